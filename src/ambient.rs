@@ -0,0 +1,62 @@
+use crate::color::Color;
+use crate::ray::{Ray, RayKind, count_ray};
+use crate::ray_intersect::{Hit, Occlusion, RayIntersect, any_occlusion};
+use crate::renderer::RenderSettings;
+use crate::sampling::{Sampler, cosine_hemisphere};
+
+// Luz ambiental con oclusión trazada: cada punto lanza rayos al hemisferio de su
+// normal y se oscurece según cuántos chocan con algo dentro de `occlusion_radius`.
+// Las muestras y el radio de la escena son los valores por defecto; los de
+// RenderSettings, si los hay, mandan.
+#[derive(Debug, Clone)]
+pub struct AmbientLight {
+    pub color: Color,
+    pub intensity: f32,
+    pub occlusion_samples: u32, // 0 = luz ambiental sin oclusión
+    pub occlusion_radius: f32,
+}
+
+impl AmbientLight {
+    pub fn new(color: Color, intensity: f32) -> Self {
+        AmbientLight {
+            color,
+            intensity,
+            occlusion_samples: 0,
+            occlusion_radius: 0.0,
+        }
+    }
+
+    pub fn with_occlusion(mut self, samples: u32, radius: f32) -> Self {
+        self.occlusion_samples = samples;
+        self.occlusion_radius = radius;
+        self
+    }
+
+    // Fracción del hemisferio que no está tapada: 1 = al descubierto, 0 = totalmente ocluido
+    pub fn visibility(&self, hit: &Hit, settings: &RenderSettings, objects: &[Box<dyn RayIntersect>], sampler: &mut Sampler) -> f32 {
+        let samples = settings.ao_samples.unwrap_or(self.occlusion_samples);
+        let radius = settings.ao_radius.unwrap_or(self.occlusion_radius);
+        if samples == 0 || radius <= 0.0 {
+            return 1.0;
+        }
+
+        let origin = hit.point + hit.normal * settings.origin_bias;
+        let mut unoccluded = 0;
+
+        for _ in 0..samples {
+            let direction = cosine_hemisphere(&hit.normal, sampler);
+            let ray = Ray::new(origin, direction).with_range(0.0, radius);
+            count_ray(RayKind::Shadow);
+            if any_occlusion(objects, &ray) != Occlusion::Opaque {
+                unoccluded += 1;
+            }
+        }
+
+        unoccluded as f32 / samples as f32
+    }
+
+    // Aporte ambiental sobre una superficie del color dado
+    pub fn shade(&self, surface_color: Color, visibility: f32) -> Color {
+        surface_color * self.color * (self.intensity * visibility)
+    }
+}
@@ -0,0 +1,328 @@
+use nalgebra_glm::{Quat, Vec3};
+use serde::Deserialize;
+use crate::color::Color;
+use crate::light::Light;
+use crate::material::Material;
+use crate::ray_intersect::RayIntersect;
+
+// Cómo se pasa de un keyframe al siguiente
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Interpolation {
+    Step,
+    Linear,
+    Cubic,     // Catmull-Rom con los keyframes vecinos
+    EaseInOut,
+}
+
+// Valores que se pueden interpolar en un track
+pub trait Animatable: Clone {
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+
+    // Catmull-Rom entre `p1` y `p2`. Barry-Goldman llama a lerp con t fuera de
+    // [0, 1], así que los tipos que redondean o recortan lo reimplementan.
+    fn catmull_rom(p0: &Self, p1: &Self, p2: &Self, p3: &Self, t: f32) -> Self {
+        barry_goldman(p0, p1, p2, p3, t)
+    }
+}
+
+impl Animatable for f32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Animatable for Vec3 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Animatable for Color {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self.interpolate(other, t)
+    }
+
+    // En f32 y recortado una sola vez al final
+    fn catmull_rom(p0: &Self, p1: &Self, p2: &Self, p3: &Self, t: f32) -> Self {
+        let [p0, p1, p2, p3] = [p0, p1, p2, p3].map(|color| Vec3::from(color.to_rgb()));
+        Color::from_rgb(Vec3::catmull_rom(&p0, &p1, &p2, &p3, t).into())
+    }
+}
+
+// Interpolación normalizada por el camino más corto; alcanza para rotaciones
+// cercanas entre keyframes y, a diferencia de slerp, vale para t fuera de [0, 1]
+impl Animatable for Quat {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        let other = if self.coords.dot(&other.coords) < 0.0 { -other.coords } else { other.coords };
+        Quat::from(self.coords.lerp(&other, t).normalize())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Keyframe<T> {
+    pub time: f32,
+    pub value: T,
+    pub interpolation: Interpolation, // Hacia el keyframe siguiente
+}
+
+#[derive(Debug, Clone)]
+pub struct Track<T> {
+    pub keys: Vec<Keyframe<T>>,
+    pub looping: bool,
+}
+
+impl<T: Animatable> Track<T> {
+    pub fn new() -> Self {
+        Track { keys: Vec::new(), looping: false }
+    }
+
+    pub fn key(mut self, time: f32, value: T, interpolation: Interpolation) -> Self {
+        let index = self.keys.partition_point(|k| k.time <= time);
+        self.keys.insert(index, Keyframe { time, value, interpolation });
+        self
+    }
+
+    // El track se repite con periodo igual al tiempo del último keyframe
+    pub fn looped(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let first = self.keys.first()?;
+        let last = self.keys.last()?;
+
+        let time = if self.looping && last.time > first.time {
+            first.time + (time - first.time).rem_euclid(last.time - first.time)
+        } else {
+            time
+        };
+
+        if time <= first.time {
+            return Some(first.value.clone());
+        }
+        if time >= last.time {
+            return Some(last.value.clone());
+        }
+
+        let i = self.keys.partition_point(|k| k.time <= time) - 1;
+        let (a, b) = (&self.keys[i], &self.keys[i + 1]);
+        let t = (time - a.time) / (b.time - a.time);
+
+        Some(match a.interpolation {
+            Interpolation::Step => a.value.clone(),
+            Interpolation::Linear => a.value.lerp(&b.value, t),
+            Interpolation::EaseInOut => a.value.lerp(&b.value, t * t * (3.0 - 2.0 * t)),
+            Interpolation::Cubic => {
+                let before = &self.keys[i.saturating_sub(1)].value;
+                let after = &self.keys[(i + 2).min(self.keys.len() - 1)].value;
+                catmull_rom(before, &a.value, &b.value, after, t)
+            }
+        })
+    }
+}
+
+impl<T: Animatable> Default for Track<T> {
+    fn default() -> Self {
+        Track::new()
+    }
+}
+
+// Catmull-Rom uniforme entre `p1` y `p2`
+pub fn catmull_rom<T: Animatable>(p0: &T, p1: &T, p2: &T, p3: &T, t: f32) -> T {
+    T::catmull_rom(p0, p1, p2, p3, t)
+}
+
+// Formulación de Barry-Goldman, que solo necesita lerp
+fn barry_goldman<T: Animatable>(p0: &T, p1: &T, p2: &T, p3: &T, t: f32) -> T {
+    let a1 = p0.lerp(p1, t + 1.0);
+    let a2 = p1.lerp(p2, t);
+    let a3 = p2.lerp(p3, t - 1.0);
+    let b1 = a1.lerp(&a2, (t + 1.0) / 2.0);
+    let b2 = a2.lerp(&a3, t / 2.0);
+    b1.lerp(&b2, t)
+}
+
+// Bézier cúbica por de Casteljau
+pub fn cubic_bezier<T: Animatable>(p0: &T, p1: &T, p2: &T, p3: &T, t: f32) -> T {
+    let a1 = p0.lerp(p1, t);
+    let a2 = p1.lerp(p2, t);
+    let a3 = p2.lerp(p3, t);
+    let b1 = a1.lerp(&a2, t);
+    let b2 = a2.lerp(&a3, t);
+    b1.lerp(&b2, t)
+}
+
+// Tracks opcionales para las propiedades de una luz
+#[derive(Debug, Clone, Default)]
+pub struct LightAnimation {
+    pub position: Option<Track<Vec3>>,
+    pub color: Option<Track<Color>>,
+    pub intensity: Option<Track<f32>>,
+}
+
+impl LightAnimation {
+    pub fn apply(&self, light: &mut Light, time: f32) {
+        if let Some(position) = self.position.as_ref().and_then(|t| t.sample(time)) {
+            light.position = position;
+        }
+        if let Some(color) = self.color.as_ref().and_then(|t| t.sample(time)) {
+            light.color = color;
+        }
+        if let Some(intensity) = self.intensity.as_ref().and_then(|t| t.sample(time)) {
+            light.intensity = intensity;
+        }
+    }
+}
+
+// Tracks opcionales para los parámetros de un material
+#[derive(Debug, Clone, Default)]
+pub struct MaterialAnimation {
+    pub diffuse: Option<Track<Color>>,
+    pub specular: Option<Track<f32>>,
+    pub emission: Option<Track<Color>>,
+    pub emission_strength: Option<Track<f32>>,
+}
+
+impl MaterialAnimation {
+    pub fn apply(&self, material: &mut Material, time: f32) {
+        if let Some(diffuse) = self.diffuse.as_ref().and_then(|t| t.sample(time)) {
+            material.diffuse = diffuse;
+        }
+        if let Some(specular) = self.specular.as_ref().and_then(|t| t.sample(time)) {
+            material.specular = specular;
+        }
+        if let Some(emission) = self.emission.as_ref().and_then(|t| t.sample(time)) {
+            material.emission = emission;
+        }
+        if let Some(strength) = self.emission_strength.as_ref().and_then(|t| t.sample(time)) {
+            material.emission_strength = strength;
+        }
+    }
+}
+
+// Reúne las animaciones de la escena. Los objetos guardan sus propios tracks
+// (offset, rotación y escala de un Group, material de un Cube) y se evalúan con `update`; las
+// luces se animan desde aquí por su índice en la lista de luces.
+#[derive(Default)]
+pub struct Timeline {
+    pub lights: Vec<(usize, LightAnimation)>,
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Timeline::default()
+    }
+
+    pub fn with_light(mut self, index: usize, animation: LightAnimation) -> Self {
+        self.lights.push((index, animation));
+        self
+    }
+
+    pub fn apply(&self, time: f32, objects: &mut [Box<dyn RayIntersect>], lights: &mut [Light]) {
+        for object in objects.iter_mut() {
+            object.update(time);
+        }
+        for (index, animation) in &self.lights {
+            if let Some(light) = lights.get_mut(*index) {
+                animation.apply(light, time);
+            }
+        }
+    }
+}
+
+// Lo que avanza `step`: un cuadro a 30 fps
+const STEP: f32 = 1.0 / 30.0;
+
+// Tiempo de la escena en la ventana, independiente del reloj real: se puede
+// pausar, mover a mano o avanzar de a un cuadro. La exportación no lo usa; saca
+// el tiempo de cada cuadro de su número.
+#[derive(Debug, Clone)]
+pub struct Clock {
+    pub time: f32,
+    pub speed: f32,
+    pub paused: bool,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Clock { time: 0.0, speed: 1.0, paused: false }
+    }
+
+    pub fn tick(&mut self, real_delta: f32) {
+        if !self.paused {
+            self.time += real_delta * self.speed;
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn scrub(&mut self, delta: f32) {
+        self.time = (self.time + delta).max(0.0);
+    }
+
+    pub fn seek(&mut self, time: f32) {
+        self.time = time.max(0.0);
+    }
+
+    // Avanza un solo cuadro aunque el reloj esté en pausa
+    pub fn step(&mut self) {
+        self.time += STEP;
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::cube::Cube;
+    use crate::group::Group;
+
+    #[test]
+    fn los_colores_cubicos_no_se_recortan_en_el_camino() {
+        let track = [0, 200, 250, 250]
+            .iter()
+            .enumerate()
+            .fold(Track::new(), |track, (time, &red)| track.key(time as f32, Color::new(red, 0, 0), Interpolation::Cubic));
+        // Catmull-Rom da 237.5 a mitad del segundo tramo
+        assert_eq!(track.sample(1.5).unwrap().to_hex() >> 16, 238);
+        assert_eq!(track.sample(2.0).unwrap().to_hex() >> 16, 250);
+    }
+
+    #[test]
+    fn los_grupos_siguen_rotacion_y_escala() {
+        let cube = Cube::new(Vec3::new(1.0, 0.0, 0.0), Vec3::new(2.0, 1.0, 1.0), Arc::new(Material::black()));
+        let quarter_turn = nalgebra_glm::quat_angle_axis(std::f32::consts::FRAC_PI_2, &Vec3::new(0.0, 1.0, 0.0));
+        let rotation = Track::new()
+            .key(0.0, Quat::identity(), Interpolation::Linear)
+            .key(1.0, quarter_turn, Interpolation::Linear);
+        let scale = Track::new()
+            .key(0.0, Vec3::repeat(1.0), Interpolation::Step)
+            .key(1.0, Vec3::repeat(2.0), Interpolation::Step);
+        let mut group = Group::new(vec![Box::new(cube)], Vec3::zeros())
+            .with_rotation_track(rotation)
+            .with_scale_track(scale);
+
+        // Un cuarto de vuelta en Y lleva +X a -Z, y la escala lo duplica
+        group.update(1.0);
+        let bounds = group.bounds().unwrap();
+        assert!((bounds.min - Vec3::new(0.0, 0.0, -4.0)).norm() < 1e-4, "{:?}", bounds);
+        assert!((bounds.max - Vec3::new(2.0, 2.0, -2.0)).norm() < 1e-4, "{:?}", bounds);
+
+        // A mitad de camino la rotación es de 45° y la escala sigue en 1
+        group.update(0.5);
+        let expected = nalgebra_glm::quat_to_mat3(&nalgebra_glm::quat_angle_axis(std::f32::consts::FRAC_PI_4, &Vec3::new(0.0, 1.0, 0.0)));
+        let corner = expected * Vec3::new(1.0, 0.0, 0.0);
+        let bounds = group.bounds().unwrap();
+        assert!((bounds.min.x - corner.x).abs() < 1e-4, "{:?}", bounds);
+    }
+}
@@ -0,0 +1,319 @@
+use std::fs;
+use std::path::Path;
+
+use exr::prelude::{
+    AnyChannel, AnyChannels, Encoding, FlatSamples, Image, ImageAttributes, IntegerBounds, Layer,
+    LayerAttributes, WritableImage,
+};
+use image::{Rgb, RgbImage};
+use nalgebra_glm::Vec3;
+
+use crate::color::Color;
+
+// Variables de salida (AOVs): buffers auxiliares que se llenan durante el render
+// del impacto primario, para componer o depurar por separado.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aov {
+    Depth,      // distancia a lo largo del eje de la cámara
+    Normal,     // normal exterior en espacio de mundo
+    Albedo,     // color de la superficie, con textura, sin iluminar
+    Position,   // punto de impacto en espacio de mundo
+    Uv,
+    ObjectId,
+    MaterialId,
+    Direct,     // luz directa, ambiental y emisión
+    Reflection, // aporte del rayo reflejado
+    Refraction, // aporte del rayo refractado
+}
+
+impl Aov {
+    pub const ALL: [Aov; 10] = [
+        Aov::Depth,
+        Aov::Normal,
+        Aov::Albedo,
+        Aov::Position,
+        Aov::Uv,
+        Aov::ObjectId,
+        Aov::MaterialId,
+        Aov::Direct,
+        Aov::Reflection,
+        Aov::Refraction,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Aov::Depth => "depth",
+            Aov::Normal => "normal",
+            Aov::Albedo => "albedo",
+            Aov::Position => "position",
+            Aov::Uv => "uv",
+            Aov::ObjectId => "object_id",
+            Aov::MaterialId => "material_id",
+            Aov::Direct => "direct",
+            Aov::Reflection => "reflection",
+            Aov::Refraction => "refraction",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Aov> {
+        Aov::ALL.into_iter().find(|aov| aov.name() == name)
+    }
+
+    // Lista separada por comas, o "all"
+    pub fn parse_list(list: &str) -> Result<Vec<Aov>, String> {
+        if list == "all" {
+            return Ok(Aov::ALL.to_vec());
+        }
+        list.split(',')
+            .map(|name| Aov::from_name(name.trim()).ok_or_else(|| format!("AOV desconocido: {}", name)))
+            .collect()
+    }
+
+    fn channels(self) -> &'static [&'static str] {
+        match self {
+            Aov::Depth => &["Z"],
+            Aov::Normal | Aov::Position => &["X", "Y", "Z"],
+            Aov::Albedo | Aov::Direct | Aov::Reflection | Aov::Refraction => &["R", "G", "B"],
+            Aov::Uv => &["U", "V"],
+            Aov::ObjectId | Aov::MaterialId => &["id"],
+        }
+    }
+
+    fn values(self, sample: &AovSample) -> [f32; 3] {
+        match self {
+            Aov::Depth => [sample.depth, 0.0, 0.0],
+            Aov::Normal => sample.normal.into(),
+            Aov::Albedo => color_to_linear(sample.albedo),
+            Aov::Position => sample.position.into(),
+            Aov::Uv => [sample.uv.0, sample.uv.1, 0.0],
+            Aov::ObjectId => [sample.object_id as f32, 0.0, 0.0],
+            Aov::MaterialId => [sample.material_id as f32, 0.0, 0.0],
+            Aov::Direct => color_to_linear(sample.direct),
+            Aov::Reflection => color_to_linear(sample.reflection),
+            Aov::Refraction => color_to_linear(sample.refraction),
+        }
+    }
+}
+
+// Los colores del render están en sRGB de 8 bits; las capas de color se guardan
+// lineales, que es lo que espera un EXR
+fn color_to_linear(color: Color) -> [f32; 3] {
+    color.to_rgb().map(|c| srgb_to_linear(c / 255.0))
+}
+
+// Función de transferencia de sRGB, de 0..1 codificado a 0..1 lineal y de vuelta
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 }
+}
+
+// Lo que cast_ray sabe del impacto primario de un píxel. Si el rayo no choca con
+// nada, la profundidad es infinita y el cielo queda en `direct`.
+#[derive(Debug, Clone, Copy)]
+pub struct AovSample {
+    pub depth: f32,
+    pub normal: Vec3,
+    pub albedo: Color,
+    pub position: Vec3,
+    pub uv: (f32, f32),
+    pub object_id: u32,
+    pub material_id: u32,
+    pub direct: Color,
+    pub reflection: Color,
+    pub refraction: Color,
+}
+
+impl Default for AovSample {
+    fn default() -> Self {
+        AovSample {
+            depth: f32::INFINITY,
+            normal: Vec3::zeros(),
+            albedo: Color::black(),
+            position: Vec3::zeros(),
+            uv: (0.0, 0.0),
+            object_id: 0,
+            material_id: 0,
+            direct: Color::black(),
+            reflection: Color::black(),
+            refraction: Color::black(),
+        }
+    }
+}
+
+struct AovLayer {
+    aov: Aov,
+    data: Vec<f32>, // canales intercalados, Aov::channels() por píxel
+}
+
+pub struct AovBuffers {
+    pub width: usize,
+    pub height: usize,
+    layers: Vec<AovLayer>,
+}
+
+impl AovBuffers {
+    pub fn new(width: usize, height: usize, aovs: &[Aov]) -> Self {
+        let mut layers: Vec<AovLayer> = Vec::new();
+        for &aov in aovs {
+            if layers.iter().all(|layer| layer.aov != aov) {
+                let data = vec![0.0; width * height * aov.channels().len()];
+                layers.push(AovLayer { aov, data });
+            }
+        }
+        AovBuffers { width, height, layers }
+    }
+
+    pub fn store(&mut self, x: usize, y: usize, sample: &AovSample) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        for layer in &mut self.layers {
+            let channels = layer.aov.channels().len();
+            let start = (y * self.width + x) * channels;
+            let values = layer.aov.values(sample);
+            layer.data[start..start + channels].copy_from_slice(&values[..channels]);
+        }
+    }
+
+    // Valores crudos de una capa, con los canales de cada píxel seguidos
+    pub fn layer(&self, aov: Aov) -> Option<&[f32]> {
+        self.layers.iter().find(|layer| layer.aov == aov).map(|layer| layer.data.as_slice())
+    }
+
+    // Un PNG por AOV, remapeado a un rango visible: `<prefijo><nombre>.png`
+    pub fn save_images(&self, dir: &Path, prefix: &str) -> Result<(), String> {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        for layer in &self.layers {
+            let path = dir.join(format!("{}{}.png", prefix, layer.aov.name()));
+            self.preview(layer).save(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+        Ok(())
+    }
+
+    // Todas las capas en un solo EXR de 32 bits, una capa por AOV. Los colores,
+    // incluido el beauty, van lineales.
+    pub fn save_exr(&self, path: &Path, beauty: &[u32]) -> Result<(), String> {
+        let size = (self.width, self.height);
+        let mut layers = vec![Layer::new(
+            size,
+            LayerAttributes::named("beauty"),
+            Encoding::FAST_LOSSLESS,
+            AnyChannels::sort(
+                ["R", "G", "B"]
+                    .iter()
+                    .enumerate()
+                    .map(|(c, name)| {
+                        let shift = 16 - 8 * c;
+                        let samples = beauty
+                            .iter()
+                            .map(|pixel| srgb_to_linear(((pixel >> shift) & 0xFF) as f32 / 255.0))
+                            .collect();
+                        AnyChannel::new(*name, FlatSamples::F32(samples))
+                    })
+                    .collect(),
+            ),
+        )];
+
+        for layer in &self.layers {
+            let names = layer.aov.channels();
+            let channels = names
+                .iter()
+                .enumerate()
+                .map(|(c, name)| {
+                    let samples = layer.data.iter().skip(c).step_by(names.len()).copied().collect();
+                    AnyChannel::new(*name, FlatSamples::F32(samples))
+                })
+                .collect();
+            layers.push(Layer::new(
+                size,
+                LayerAttributes::named(layer.aov.name()),
+                Encoding::FAST_LOSSLESS,
+                AnyChannels::sort(channels),
+            ));
+        }
+
+        let image = Image::from_layers(ImageAttributes::new(IntegerBounds::from_dimensions(size)), layers);
+        image.write().to_file(path).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn preview(&self, layer: &AovLayer) -> RgbImage {
+        let channels = layer.aov.channels().len();
+        let pixel = |x: u32, y: u32| {
+            let start = (y as usize * self.width + x as usize) * channels;
+            &layer.data[start..start + channels]
+        };
+        let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        match layer.aov {
+            Aov::Depth => {
+                // Cerca en blanco, lejos en negro; el cielo queda negro
+                let (near, far) = finite_range(layer.data.iter().copied());
+                RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+                    let depth = pixel(x, y)[0];
+                    let v = if depth.is_finite() { 1.0 - (depth - near) / (far - near).max(1e-6) } else { 0.0 };
+                    Rgb([to_u8(v); 3])
+                })
+            }
+            Aov::Normal => RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+                let n = pixel(x, y);
+                Rgb([0, 1, 2].map(|c| to_u8(n[c] * 0.5 + 0.5)))
+            }),
+            Aov::Position => {
+                let ranges = [0, 1, 2].map(|c| finite_range(layer.data.iter().skip(c).step_by(3).copied()));
+                RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+                    let p = pixel(x, y);
+                    Rgb([0, 1, 2].map(|c| {
+                        let (min, max) = ranges[c];
+                        to_u8((p[c] - min) / (max - min).max(1e-6))
+                    }))
+                })
+            }
+            Aov::Uv => RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+                let uv = pixel(x, y);
+                Rgb([to_u8(uv[0]), to_u8(uv[1]), 0])
+            }),
+            Aov::ObjectId | Aov::MaterialId => RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+                Rgb(id_color(pixel(x, y)[0] as u32))
+            }),
+            // De vuelta a sRGB, como se ven en el render
+            Aov::Albedo | Aov::Direct | Aov::Reflection | Aov::Refraction => {
+                RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+                    let c = pixel(x, y);
+                    Rgb([0, 1, 2].map(|i| to_u8(linear_to_srgb(c[i].clamp(0.0, 1.0)))))
+                })
+            }
+        }
+    }
+}
+
+fn finite_range(values: impl Iterator<Item = f32>) -> (f32, f32) {
+    values
+        .filter(|v| v.is_finite())
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), v| (min.min(v), max.max(v)))
+}
+
+// Color estable y bien separado para cada id; el 0 (nada) es negro
+pub fn id_color(id: u32) -> [u8; 3] {
+    if id == 0 {
+        return [0, 0, 0];
+    }
+    let hash = id.wrapping_mul(0x9E37_79B9).rotate_left(13).wrapping_mul(0x85EB_CA6B);
+    [(hash >> 24) as u8 | 0x40, (hash >> 16) as u8 | 0x40, (hash >> 8) as u8 | 0x40]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn los_colores_se_guardan_lineales() {
+        let [r, g, b] = color_to_linear(Color::new(255, 128, 0));
+        assert_eq!((r, b), (1.0, 0.0));
+        // 128 en sRGB es apenas un 21.6% de luz
+        assert!((g - 0.2158).abs() < 1e-3, "{}", g);
+        assert!(((linear_to_srgb(g) * 255.0) - 128.0).abs() < 1e-2);
+    }
+}
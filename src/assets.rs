@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::material::Material;
+use crate::texture::Texture;
+
+// Registro de materiales y texturas compartidos. Los objetos guardan un Arc al
+// recurso, así que clonar un material o una textura solo incrementa un contador.
+#[derive(Default)]
+pub struct Assets {
+    textures: HashMap<String, Arc<Texture>>,
+    materials: HashMap<String, Arc<Material>>,
+    next_id: u32,
+}
+
+impl Assets {
+    pub fn new() -> Self {
+        Assets::default()
+    }
+
+    // Carga la textura una sola vez por ruta
+    pub fn texture(&mut self, path: &str) -> Result<Arc<Texture>, String> {
+        if let Some(texture) = self.textures.get(path) {
+            return Ok(texture.clone());
+        }
+        let texture = Arc::new(Texture::from_file(path)?);
+        self.textures.insert(path.to_string(), texture.clone());
+        Ok(texture)
+    }
+
+    // Cada material registrado recibe un id distinto; los nombres no se repiten
+    pub fn add_material(&mut self, name: &str, mut material: Material) -> Result<Arc<Material>, String> {
+        if self.materials.contains_key(name) {
+            return Err(format!("material repetido: {}", name));
+        }
+        self.next_id += 1;
+        material.id = self.next_id;
+        let material = Arc::new(material);
+        self.materials.insert(name.to_string(), material.clone());
+        Ok(material)
+    }
+
+    // Material de un archivo importado: se registra una sola vez por clave, así
+    // importar dos veces el mismo archivo comparte sus materiales
+    pub fn imported_material(&mut self, key: &str, make: impl FnOnce() -> Material) -> Arc<Material> {
+        match self.material(key) {
+            Some(material) => material,
+            None => self.add_material(key, make()).expect("la clave no estaba registrada"),
+        }
+    }
+
+    pub fn material(&self, name: &str) -> Option<Arc<Material>> {
+        self.materials.get(name).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn plain() -> Material {
+        Material::new(Color::new(255, 255, 255), 10.0, [0.9, 0.2, 0.0, 0.0], 1.0, None)
+    }
+
+    #[test]
+    fn los_ids_no_se_repiten() {
+        let mut assets = Assets::new();
+        let a = assets.add_material("a", plain()).unwrap();
+        assert!(assets.add_material("a", plain()).is_err());
+        let b = assets.add_material("b", plain()).unwrap();
+        let c = assets.imported_material("c", plain);
+        assert_eq!([a.id, b.id, c.id], [1, 2, 3]);
+        // Importar de nuevo la misma clave comparte el material
+        assert!(Arc::ptr_eq(&assets.imported_material("c", plain), &c));
+    }
+}
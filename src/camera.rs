@@ -2,10 +2,12 @@
 use nalgebra_glm::Vec3;
 use std::f32::consts::PI;
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Camera {
     pub eye: Vec3,
     pub center: Vec3,
-    pub up: Vec3
+    pub up: Vec3,
+    pub fov: f32, // Campo de visión vertical, en radianes
 }
 
 impl Camera {
@@ -13,18 +15,48 @@ impl Camera {
         Camera {
             eye,
             center,
-            up
+            up,
+            fov: PI / 3.0,
         }
     }
 
-    pub fn base_change(&self, vector: &Vec3) -> Vec3 {
+    pub fn with_fov(mut self, fov: f32) -> Self {
+        self.fov = fov;
+        self
+    }
+
+    // Ejes de la cámara en espacio de mundo: (derecha, arriba, adelante)
+    pub fn basis(&self) -> (Vec3, Vec3, Vec3) {
         let forward = (self.center - self.eye).normalize();
         let right = forward.cross(&self.up).normalize();
         let up = right.cross(&forward).normalize();
+        (right, up, forward)
+    }
+
+    pub fn base_change(&self, vector: &Vec3) -> Vec3 {
+        let (right, up, forward) = self.basis();
 
         let rotated = vector.x * right + vector.y * up - vector.z * forward;
 
-        return rotated.normalize();
+        rotated.normalize()
+    }
+
+    // Inverso del rayo primario: píxel (con decimales) donde se ve el punto, o
+    // None si está detrás de la cámara
+    pub fn project(&self, point: &Vec3, width: usize, height: usize) -> Option<(f32, f32)> {
+        let (right, up, forward) = self.basis();
+        let relative = point - self.eye;
+        let depth = relative.dot(&forward);
+        if depth <= 1e-4 {
+            return None;
+        }
+
+        let aspect_ratio = width as f32 / height as f32;
+        let perspective_scale = (self.fov * 0.5).tan();
+        let screen_x = relative.dot(&right) / depth / (aspect_ratio * perspective_scale);
+        let screen_y = relative.dot(&up) / depth / perspective_scale;
+
+        Some(((screen_x + 1.0) * width as f32 * 0.5, (1.0 - screen_y) * height as f32 * 0.5))
     }
 
     pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
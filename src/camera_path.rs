@@ -0,0 +1,251 @@
+use nalgebra_glm::Vec3;
+use serde::Deserialize;
+use crate::animation::{Animatable, catmull_rom, cubic_bezier};
+use crate::camera::Camera;
+
+// Muestras por segmento para la tabla de longitud de arco
+const ARC_SAMPLES_PER_SEGMENT: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PathInterpolation {
+    CatmullRom, // La curva pasa por todos los puntos de control
+    Bezier,     // Tramos cúbicos: punto, control, control, punto, control, ...
+}
+
+// Punto de control de la cámara
+#[derive(Debug, Clone, Copy)]
+pub struct CameraKey {
+    pub eye: Vec3,
+    pub center: Vec3,
+    pub up: Vec3,
+    pub fov: f32,
+}
+
+impl Animatable for CameraKey {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        CameraKey {
+            eye: self.eye.lerp(&other.eye, t),
+            center: self.center.lerp(&other.center, t),
+            up: self.up.lerp(&other.up, t),
+            fov: self.fov.lerp(&other.fov, t),
+        }
+    }
+}
+
+// Recorrido de cámara para tomas repetibles. Se recorre a velocidad constante:
+// el tiempo se convierte en distancia sobre la curva con una tabla de longitud de arco.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "CameraPathDesc")]
+pub struct CameraPath {
+    pub keys: Vec<CameraKey>,
+    pub interpolation: PathInterpolation,
+    pub duration: f32,
+    pub look_at: Option<Vec3>, // Si está, la cámara siempre mira a este punto
+    pub looping: bool,
+    arc_table: Vec<(f32, f32)>, // (parámetro de la curva, distancia acumulada)
+}
+
+impl CameraPath {
+    pub fn new(keys: Vec<CameraKey>, interpolation: PathInterpolation, duration: f32) -> Result<Self, String> {
+        match interpolation {
+            PathInterpolation::CatmullRom if keys.len() < 2 => {
+                return Err("un recorrido catmull-rom necesita al menos 2 puntos".to_string());
+            }
+            PathInterpolation::Bezier if keys.len() < 4 || !(keys.len() - 1).is_multiple_of(3) => {
+                return Err("un recorrido bezier necesita 3n + 1 puntos (punto, control, control, punto...)".to_string());
+            }
+            _ => {}
+        }
+        if duration <= 0.0 {
+            return Err("la duración del recorrido debe ser positiva".to_string());
+        }
+
+        let mut path = CameraPath {
+            keys,
+            interpolation,
+            duration,
+            look_at: None,
+            looping: false,
+            arc_table: Vec::new(),
+        };
+        path.build_arc_table();
+        Ok(path)
+    }
+
+    pub fn with_look_at(mut self, target: Vec3) -> Self {
+        self.look_at = Some(target);
+        self
+    }
+
+    // Cierra la curva (catmull-rom) y repite el recorrido al terminar
+    pub fn looped(mut self) -> Self {
+        self.looping = true;
+        self.build_arc_table();
+        self
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        toml::from_str(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn length(&self) -> f32 {
+        self.arc_table.last().map_or(0.0, |&(_, s)| s)
+    }
+
+    fn segment_count(&self) -> usize {
+        match self.interpolation {
+            PathInterpolation::CatmullRom if self.looping => self.keys.len(),
+            PathInterpolation::CatmullRom => self.keys.len() - 1,
+            PathInterpolation::Bezier => (self.keys.len() - 1) / 3,
+        }
+    }
+
+    // Punto de control con índice circular si el recorrido es cerrado
+    fn key(&self, index: isize) -> &CameraKey {
+        let n = self.keys.len() as isize;
+        let index = if self.looping {
+            index.rem_euclid(n)
+        } else {
+            index.clamp(0, n - 1)
+        };
+        &self.keys[index as usize]
+    }
+
+    // Evalúa la curva en u ∈ [0, segmentos]
+    fn evaluate(&self, u: f32) -> CameraKey {
+        let segments = self.segment_count();
+        let segment = (u.floor() as usize).min(segments - 1);
+        let t = u - segment as f32;
+
+        match self.interpolation {
+            PathInterpolation::CatmullRom => {
+                let i = segment as isize;
+                catmull_rom(self.key(i - 1), self.key(i), self.key(i + 1), self.key(i + 2), t)
+            }
+            PathInterpolation::Bezier => {
+                let i = segment * 3;
+                cubic_bezier(&self.keys[i], &self.keys[i + 1], &self.keys[i + 2], &self.keys[i + 3], t)
+            }
+        }
+    }
+
+    fn build_arc_table(&mut self) {
+        let samples = self.segment_count() * ARC_SAMPLES_PER_SEGMENT;
+        let mut table = Vec::with_capacity(samples + 1);
+        let mut previous = self.evaluate(0.0).eye;
+        let mut distance = 0.0;
+
+        table.push((0.0, 0.0));
+        for i in 1..=samples {
+            let u = i as f32 / ARC_SAMPLES_PER_SEGMENT as f32;
+            let eye = self.evaluate(u).eye;
+            distance += (eye - previous).magnitude();
+            previous = eye;
+            table.push((u, distance));
+        }
+
+        self.arc_table = table;
+    }
+
+    // Parámetro de la curva que está a la distancia `s` del inicio
+    fn parameter_at_distance(&self, s: f32) -> f32 {
+        let i = self.arc_table.partition_point(|&(_, distance)| distance < s);
+        if i == 0 {
+            return 0.0;
+        }
+        if i >= self.arc_table.len() {
+            return self.arc_table.last().map_or(0.0, |&(u, _)| u);
+        }
+
+        let (u0, s0) = self.arc_table[i - 1];
+        let (u1, s1) = self.arc_table[i];
+        if s1 - s0 <= f32::EPSILON {
+            u0
+        } else {
+            u0 + (u1 - u0) * (s - s0) / (s1 - s0)
+        }
+    }
+
+    pub fn key_at(&self, time: f32) -> CameraKey {
+        let fraction = if self.looping {
+            time.rem_euclid(self.duration) / self.duration
+        } else {
+            (time / self.duration).clamp(0.0, 1.0)
+        };
+
+        let mut key = self.evaluate(self.parameter_at_distance(fraction * self.length()));
+        if let Some(target) = self.look_at {
+            key.center = target;
+        }
+        key
+    }
+
+    pub fn camera_at(&self, time: f32) -> Camera {
+        let key = self.key_at(time);
+        Camera::new(key.eye, key.center, key.up.normalize()).with_fov(key.fov)
+    }
+}
+
+// Formato en archivo (TOML) de un recorrido de cámara; el FOV va en grados
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraPathDesc {
+    #[serde(default = "default_interpolation")]
+    interpolation: PathInterpolation,
+    duration: f32,
+    #[serde(default)]
+    looping: bool,
+    look_at: Option<[f32; 3]>,
+    keys: Vec<CameraKeyDesc>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraKeyDesc {
+    eye: [f32; 3],
+    center: [f32; 3],
+    #[serde(default = "default_up")]
+    up: [f32; 3],
+    #[serde(default = "default_fov")]
+    fov: f32,
+}
+
+fn default_interpolation() -> PathInterpolation {
+    PathInterpolation::CatmullRom
+}
+
+fn default_up() -> [f32; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+fn default_fov() -> f32 {
+    60.0
+}
+
+impl TryFrom<CameraPathDesc> for CameraPath {
+    type Error = String;
+
+    fn try_from(desc: CameraPathDesc) -> Result<Self, Self::Error> {
+        let keys = desc
+            .keys
+            .iter()
+            .map(|k| CameraKey {
+                eye: Vec3::from(k.eye),
+                center: Vec3::from(k.center),
+                up: Vec3::from(k.up),
+                fov: k.fov.to_radians(),
+            })
+            .collect();
+
+        let mut path = CameraPath::new(keys, desc.interpolation, desc.duration)?;
+        if desc.looping {
+            path = path.looped();
+        }
+        if let Some(target) = desc.look_at {
+            path = path.with_look_at(Vec3::from(target));
+        }
+        Ok(path)
+    }
+}
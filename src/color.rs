@@ -23,10 +23,24 @@ impl Color {
         Color { r: 0, g: 0, b: 0 }
     }
 
-    pub fn to_hex(&self) -> u32 {
+    pub fn to_hex(self) -> u32 {
         ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
     }
 
+    // Promedio canal por canal; negro si no hay colores
+    pub fn average(colors: &[Color]) -> Color {
+        if colors.is_empty() {
+            return Color::black();
+        }
+        let n = colors.len() as u32;
+        let sum = |channel: fn(&Color) -> u8| colors.iter().map(|c| channel(c) as u32).sum::<u32>();
+        Color {
+            r: ((sum(|c| c.r) + n / 2) / n) as u8,
+            g: ((sum(|c| c.g) + n / 2) / n) as u8,
+            b: ((sum(|c| c.b) + n / 2) / n) as u8,
+        }
+    }
+
     pub fn interpolate(&self, other: &Color, t: f32) -> Color {
         Color {
             r: (self.r as f32 + t * (other.r as f32 - self.r as f32)).round() as u8,
@@ -34,6 +48,17 @@ impl Color {
             b: (self.b as f32 + t * (other.b as f32 - self.b as f32)).round() as u8,
         }
     }
+
+    // Canales de 0 a 255 en f32, para operar sin redondear en cada paso
+    pub fn to_rgb(self) -> [f32; 3] {
+        [self.r as f32, self.g as f32, self.b as f32]
+    }
+
+    // Redondea y recorta cada canal a [0, 255]
+    pub fn from_rgb([r, g, b]: [f32; 3]) -> Color {
+        let channel = |value: f32| value.round().clamp(0.0, 255.0) as u8;
+        Color { r: channel(r), g: channel(g), b: channel(b) }
+    }
 }
 
 use std::ops::Add;
@@ -64,6 +89,19 @@ impl Mul<f32> for Color {
     }
 }
 
+// Multiplica canal por canal, como una luz de color iluminando una superficie
+impl Mul<Color> for Color {
+    type Output = Color;
+
+    fn mul(self, other: Color) -> Color {
+        Color {
+            r: ((self.r as u16 * other.r as u16) / 255) as u8,
+            g: ((self.g as u16 * other.g as u16) / 255) as u8,
+            b: ((self.b as u16 * other.b as u16) / 255) as u8,
+        }
+    }
+}
+
 impl fmt::Display for Color {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Color(r: {}, g: {}, b: {})", self.r, self.g, self.b)
@@ -0,0 +1,359 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::aov::Aov;
+use crate::color::Color;
+use crate::denoise::Denoiser;
+use crate::export::{AovFormat, ExportFormat, ExportOptions};
+use crate::renderer::RenderSettings;
+use crate::scene::Scene;
+
+// Se lee si existe y no se pasó --config
+pub const DEFAULT_CONFIG_FILE: &str = "refractor.toml";
+const DEFAULT_SCENE: &str = "playa.toml";
+
+const USAGE: &str = "uso: proyecto2raytracing [--config <archivo>] [--scene <archivo>] [--mode window|headless] [--output <ruta>]
+  render: [--size ANCHOxALTO] [--samples N] [--max-depth N] [--origin-bias X] [--sky-color R,G,B] [--ao-samples N] [--ao-radius X] [--fov GRADOS]
+  escena: [--day-length SEGUNDOS] [--rotation-speed GRADOS]
+  headless: [--format png|gif|apng] [--fps N] [--frames A..B] [--camera-path] [--aovs all|depth,normal,...] [--aov-format png|exr] [--denoise FUERZA] [--denoise-passes N]
+  png escribe PNG numerados: <salida>_0000.png si --output termina en .png, si no frame_0000.png dentro de esa carpeta
+  sin --format se elige por la extensión: .gif, .apng o .png (secuencia); sin extensión, secuencia en una carpeta";
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Mode {
+    Window,
+    Headless, // renderiza los cuadros a --output, sin ventana
+}
+
+// Todo lo que se puede ajustar sin tocar el código. Cada valor sale de la línea
+// de comandos, si no del archivo de configuración, y si no queda el de siempre.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub scene: PathBuf,
+    pub mode: Mode,
+    pub render: RenderSettings,
+    pub fov: Option<f32>,              // radianes; reemplaza el de la cámara de la escena
+    pub day_length: Option<f32>,       // reemplaza la duración del ciclo de día de la escena
+    pub rotation_speed: f32,           // radianes por cuadro al orbitar con las flechas
+    pub export: Option<ExportOptions>, // solo en modo headless
+}
+
+impl Config {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let (config_file, cli) = ConfigDesc::from_args(args)?;
+        let file = match config_file {
+            Some(path) => ConfigDesc::from_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => ConfigDesc::from_file(Path::new(DEFAULT_CONFIG_FILE))?,
+            None => ConfigDesc::default(),
+        };
+        cli.or(file).build()
+    }
+
+    // Aplica a la escena lo que la configuración reemplaza
+    pub fn apply_to_scene(&self, scene: &mut Scene) {
+        if let Some(fov) = self.fov {
+            scene.camera.fov = fov;
+        }
+        if let Some(day_length) = self.day_length {
+            scene.day_cycle.cycle_duration = day_length;
+        }
+    }
+}
+
+// Valores tal como vienen del archivo o de la línea de comandos; ninguno es obligatorio
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigDesc {
+    scene: Option<PathBuf>,
+    mode: Option<Mode>,
+    output: Option<PathBuf>,
+    size: Option<[u32; 2]>,
+    samples: Option<u32>,
+    max_depth: Option<u32>,
+    origin_bias: Option<f32>,
+    sky_color: Option<[u8; 3]>,
+    ao_samples: Option<u32>,
+    ao_radius: Option<f32>,
+    fov: Option<f32>,            // grados
+    day_length: Option<f32>,
+    rotation_speed: Option<f32>, // grados por cuadro
+    format: Option<String>,
+    fps: Option<u32>,
+    frames: Option<[u32; 2]>,
+    camera_path: Option<bool>,
+    aovs: Option<String>,
+    aov_format: Option<String>,
+    denoise: Option<f32>,
+    denoise_passes: Option<u32>,
+}
+
+impl ConfigDesc {
+    fn from_file(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    // Devuelve también el archivo de configuración, si se pidió uno
+    fn from_args(args: &[String]) -> Result<(Option<PathBuf>, Self), String> {
+        let mut config_file = None;
+        let mut desc = ConfigDesc::default();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("falta el valor de {}\n{}", arg, USAGE));
+            match arg.as_str() {
+                "--config" => config_file = Some(PathBuf::from(value()?)),
+                "--scene" => desc.scene = Some(PathBuf::from(value()?)),
+                "--mode" => desc.mode = Some(parse_mode(value()?)?),
+                "--output" | "--export" => desc.output = Some(PathBuf::from(value()?)),
+                "--size" => desc.size = Some(parse_pair(value()?, "x", arg)?.into()),
+                "--samples" => desc.samples = Some(parse_number(value()?, arg)?),
+                "--max-depth" => desc.max_depth = Some(parse_number(value()?, arg)?),
+                "--origin-bias" => desc.origin_bias = Some(parse_float(value()?, arg)?),
+                "--sky-color" => desc.sky_color = Some(parse_color(value()?, arg)?),
+                "--ao-samples" => desc.ao_samples = Some(parse_number(value()?, arg)?),
+                "--ao-radius" => desc.ao_radius = Some(parse_float(value()?, arg)?),
+                "--fov" => desc.fov = Some(parse_float(value()?, arg)?),
+                "--day-length" => desc.day_length = Some(parse_float(value()?, arg)?),
+                "--rotation-speed" => desc.rotation_speed = Some(parse_float(value()?, arg)?),
+                "--format" => desc.format = Some(value()?.clone()),
+                "--fps" => desc.fps = Some(parse_number(value()?, arg)?),
+                "--frames" => desc.frames = Some(parse_pair(value()?, "..", arg)?.into()),
+                "--camera-path" => desc.camera_path = Some(true),
+                "--aovs" => desc.aovs = Some(value()?.clone()),
+                "--aov-format" => desc.aov_format = Some(value()?.clone()),
+                "--denoise" => desc.denoise = Some(parse_float(value()?, arg)?),
+                "--denoise-passes" => desc.denoise_passes = Some(parse_number(value()?, arg)?),
+                _ => return Err(format!("argumento desconocido: {}\n{}", arg, USAGE)),
+            }
+        }
+
+        Ok((config_file, desc))
+    }
+
+    // Lo de `self` manda; lo que falte se toma de `defaults`
+    fn or(self, defaults: ConfigDesc) -> ConfigDesc {
+        ConfigDesc {
+            scene: self.scene.or(defaults.scene),
+            mode: self.mode.or(defaults.mode),
+            output: self.output.or(defaults.output),
+            size: self.size.or(defaults.size),
+            samples: self.samples.or(defaults.samples),
+            max_depth: self.max_depth.or(defaults.max_depth),
+            origin_bias: self.origin_bias.or(defaults.origin_bias),
+            sky_color: self.sky_color.or(defaults.sky_color),
+            ao_samples: self.ao_samples.or(defaults.ao_samples),
+            ao_radius: self.ao_radius.or(defaults.ao_radius),
+            fov: self.fov.or(defaults.fov),
+            day_length: self.day_length.or(defaults.day_length),
+            rotation_speed: self.rotation_speed.or(defaults.rotation_speed),
+            format: self.format.or(defaults.format),
+            fps: self.fps.or(defaults.fps),
+            frames: self.frames.or(defaults.frames),
+            camera_path: self.camera_path.or(defaults.camera_path),
+            aovs: self.aovs.or(defaults.aovs),
+            aov_format: self.aov_format.or(defaults.aov_format),
+            denoise: self.denoise.or(defaults.denoise),
+            denoise_passes: self.denoise_passes.or(defaults.denoise_passes),
+        }
+    }
+
+    fn build(self) -> Result<Config, String> {
+        let mode = self.mode.unwrap_or(if self.output.is_some() { Mode::Headless } else { Mode::Window });
+
+        let defaults = RenderSettings::default();
+        let [width, height] = self.size.unwrap_or([defaults.width as u32, defaults.height as u32]);
+        if width == 0 || height == 0 {
+            return Err("el tamaño debe ser mayor que cero".to_string());
+        }
+        let samples = self.samples.unwrap_or(defaults.samples);
+        if samples == 0 {
+            return Err("hace falta al menos una muestra por píxel".to_string());
+        }
+        let denoiser = match (self.denoise, self.denoise_passes) {
+            (None, None) => None,
+            (strength, passes) => {
+                let denoiser = Denoiser::new(strength.unwrap_or(1.0));
+                Some(passes.map_or(denoiser, |passes| denoiser.with_iterations(passes)))
+            }
+        };
+
+        let render = RenderSettings {
+            width: width as usize,
+            height: height as usize,
+            samples,
+            max_depth: self.max_depth.unwrap_or(defaults.max_depth),
+            origin_bias: self.origin_bias.unwrap_or(defaults.origin_bias),
+            sky_color: self.sky_color.map_or(defaults.sky_color, |[r, g, b]| Color::new(r, g, b)),
+            ao_samples: self.ao_samples,
+            ao_radius: self.ao_radius,
+            aovs: self.aovs.as_deref().map(Aov::parse_list).transpose()?.unwrap_or_default(),
+            denoiser,
+        };
+
+        let export = match (mode, self.output) {
+            (Mode::Window, None) => None,
+            (Mode::Window, Some(_)) => return Err("--output solo se usa en modo headless".to_string()),
+            (Mode::Headless, None) => return Err(format!("el modo headless necesita --output\n{}", USAGE)),
+            (Mode::Headless, Some(output)) => {
+                let fps = self.fps.unwrap_or(30);
+                if fps == 0 {
+                    return Err("--fps debe ser mayor que cero".to_string());
+                }
+                let [start_frame, end_frame] = self.frames.unwrap_or([0, 2 * fps]);
+                if end_frame <= start_frame {
+                    return Err(format!("rango de cuadros vacío: {}..{}", start_frame, end_frame));
+                }
+                let format = match self.format.as_deref() {
+                    Some(format) => parse_format(format)?,
+                    None => match output.extension().and_then(|e| e.to_str()) {
+                        Some("gif") => ExportFormat::Gif,
+                        Some("apng") => ExportFormat::Apng,
+                        _ => ExportFormat::PngSequence,
+                    },
+                };
+                let aov_format = self.aov_format.as_deref().map_or(Ok(AovFormat::Exr), parse_aov_format)?;
+
+                Some(ExportOptions {
+                    output,
+                    format,
+                    fps,
+                    start_frame,
+                    end_frame,
+                    follow_camera_path: self.camera_path.unwrap_or(false),
+                    aov_format,
+                })
+            }
+        };
+
+        Ok(Config {
+            scene: self.scene.unwrap_or_else(|| PathBuf::from(DEFAULT_SCENE)),
+            mode,
+            render,
+            fov: self.fov.map(f32::to_radians),
+            day_length: self.day_length,
+            rotation_speed: self.rotation_speed.unwrap_or(18.0).to_radians(),
+            export,
+        })
+    }
+}
+
+fn parse_mode(value: &str) -> Result<Mode, String> {
+    match value {
+        "window" => Ok(Mode::Window),
+        "headless" => Ok(Mode::Headless),
+        _ => Err(format!("modo desconocido: {} (window o headless)", value)),
+    }
+}
+
+fn parse_format(value: &str) -> Result<ExportFormat, String> {
+    match value {
+        "png" => Ok(ExportFormat::PngSequence),
+        "gif" => Ok(ExportFormat::Gif),
+        "apng" => Ok(ExportFormat::Apng),
+        _ => Err(format!("formato desconocido: {} (png, gif o apng)", value)),
+    }
+}
+
+fn parse_aov_format(value: &str) -> Result<AovFormat, String> {
+    match value {
+        "png" => Ok(AovFormat::Png),
+        "exr" => Ok(AovFormat::Exr),
+        _ => Err(format!("formato de AOV desconocido: {} (png o exr)", value)),
+    }
+}
+
+fn parse_number(value: &str, flag: &str) -> Result<u32, String> {
+    value.parse().map_err(|_| format!("{} espera un entero, no {}", flag, value))
+}
+
+fn parse_float(value: &str, flag: &str) -> Result<f32, String> {
+    value.parse().map_err(|_| format!("{} espera un número, no {}", flag, value))
+}
+
+fn parse_pair(value: &str, separator: &str, flag: &str) -> Result<(u32, u32), String> {
+    let (a, b) = value
+        .split_once(separator)
+        .ok_or_else(|| format!("{} espera A{}B, no {}", flag, separator, value))?;
+    Ok((parse_number(a, flag)?, parse_number(b, flag)?))
+}
+
+fn parse_color(value: &str, flag: &str) -> Result<[u8; 3], String> {
+    let channels: Vec<u8> = value
+        .split(',')
+        .map(|c| c.trim().parse().map_err(|_| format!("{} espera R,G,B entre 0 y 255, no {}", flag, value)))
+        .collect::<Result<_, _>>()?;
+    channels
+        .try_into()
+        .map_err(|_| format!("{} espera tres canales, no {}", flag, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn build(line: &str) -> Result<Config, String> {
+        let (_, desc) = ConfigDesc::from_args(&args(line))?;
+        desc.build()
+    }
+
+    #[test]
+    fn la_linea_de_comandos_manda_sobre_el_archivo() {
+        let file: ConfigDesc = toml::from_str("samples = 4\nmax_depth = 5\nsize = [320, 240]").unwrap();
+        let (_, cli) = ConfigDesc::from_args(&args("--samples 2 --size 64x48")).unwrap();
+        let config = cli.or(file).build().unwrap();
+        assert_eq!(config.render.samples, 2);
+        assert_eq!((config.render.width, config.render.height), (64, 48));
+        assert_eq!(config.render.max_depth, 5);
+    }
+
+    #[test]
+    fn pares_y_colores() {
+        assert_eq!(parse_pair("640x480", "x", "--size"), Ok((640, 480)));
+        assert_eq!(parse_pair("10..20", "..", "--frames"), Ok((10, 20)));
+        assert!(parse_pair("640", "x", "--size").is_err());
+        assert!(parse_pair("ax2", "x", "--size").is_err());
+
+        assert_eq!(parse_color("1, 2,3", "--sky-color"), Ok([1, 2, 3]));
+        assert!(parse_color("1,2", "--sky-color").is_err());
+        assert!(parse_color("1,2,300", "--sky-color").is_err());
+    }
+
+    #[test]
+    fn rango_de_cuadros_por_defecto() {
+        let export = build("--output salida --fps 12").unwrap().export.unwrap();
+        assert_eq!((export.start_frame, export.end_frame), (0, 24));
+        assert!(build("--output salida --frames 5..5").is_err());
+        assert!(build("--output salida --fps 0").is_err());
+    }
+
+    #[test]
+    fn formato_segun_la_extension() {
+        let format = |line: &str| build(line).unwrap().export.unwrap().format;
+        assert_eq!(format("--output a.gif"), ExportFormat::Gif);
+        assert_eq!(format("--output a.apng"), ExportFormat::Apng);
+        assert_eq!(format("--output cuadros"), ExportFormat::PngSequence);
+        // Una salida .png es la secuencia numerada; el APNG se pide aparte
+        assert_eq!(format("--output a.png"), ExportFormat::PngSequence);
+        assert_eq!(format("--output a.png --format apng"), ExportFormat::Apng);
+        assert!(build("--output a.gif --format jpg").is_err());
+    }
+
+    #[test]
+    fn modo_y_salida() {
+        assert_eq!(build("").unwrap().mode, Mode::Window);
+        assert!(build("").unwrap().export.is_none());
+        assert_eq!(build("--output a.gif").unwrap().mode, Mode::Headless);
+        assert!(build("--mode headless").is_err());
+        assert!(build("--mode window --output a.gif").is_err());
+        assert!(build("--mode ventana").is_err());
+        assert!(build("--samples").is_err());
+        assert!(build("--desconocido 1").is_err());
+    }
+}
@@ -1,108 +1,141 @@
 use nalgebra_glm::{Vec3};
-use crate::ray_intersect::{RayIntersect, Intersect};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use crate::ray::Ray;
+use crate::ray_intersect::{Aabb, RayIntersect, Hit, Occlusion, count_intersection_test};
 use crate::material::Material;
+use crate::light::Light;
+use crate::animation::MaterialAnimation;
+
+// Separación de las luces muestreadas respecto a la cara, para que el propio cubo no las tape
+const EMISSIVE_LIGHT_OFFSET: f32 = 1e-3;
+// Atenuación cuadrática de las luces muestreadas; una linterna no ilumina toda la playa
+const EMISSIVE_LIGHT_ATTENUATION: f32 = 25.0;
 
 pub struct Cube {
     pub min: Vec3,       // Punto mínimo del cubo (esquina inferior izquierda)
     pub max: Vec3,       // Punto máximo del cubo (esquina superior derecha)
-    pub material: Material,
+    pub material: Arc<Material>,
+    pub id: u32,         // Identificador único, se asigna al crearlo
+    pub material_animation: Option<MaterialAnimation>,
 }
 
-impl Cube {
-    pub fn new(min: Vec3, max: Vec3, material: Material) -> Self {
-        Self { min, max, material }
-    }
+static NEXT_OBJECT_ID: AtomicU32 = AtomicU32::new(1);
+
+// Identificador para el AOV object_id; lo comparten los cubos y las grillas de voxeles
+pub(crate) fn next_object_id() -> u32 {
+    NEXT_OBJECT_ID.fetch_add(1, Ordering::Relaxed)
 }
 
-impl RayIntersect for Cube {
-    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
-        let mut t_min = (self.min.x - ray_origin.x) / ray_direction.x;
-        let mut t_max = (self.max.x - ray_origin.x) / ray_direction.x;
+impl Cube {
+    pub fn new(min: Vec3, max: Vec3, material: Arc<Material>) -> Self {
+        Self { min, max, material, id: next_object_id(), material_animation: None }
+    }
 
-        if t_min > t_max {
-            std::mem::swap(&mut t_min, &mut t_max);
-        }
+    // Anima el material de este cubo. Al animarse deja de compartirse con el
+    // resto de objetos que usan el mismo material.
+    pub fn with_material_animation(mut self, animation: MaterialAnimation) -> Self {
+        self.material_animation = Some(animation);
+        self
+    }
 
-        let mut ty_min = (self.min.y - ray_origin.y) / ray_direction.y;
-        let mut ty_max = (self.max.y - ray_origin.y) / ray_direction.y;
+    // Devuelve la distancia, el eje de la cara y si el rayo entra (true) o sale del cubo
+    fn slab_intersect(&self, ray: &Ray) -> Option<(f32, usize, bool)> {
+        count_intersection_test();
+        let (t_near, near_axis, t_far, far_axis) = Aabb::new(self.min, self.max).slab(ray)?;
 
-        if ty_min > ty_max {
-            std::mem::swap(&mut ty_min, &mut ty_max);
+        // Si el origen está dentro del cubo, la intersección válida es la de salida
+        if t_near >= ray.t_min {
+            (t_near <= ray.t_max).then_some((t_near, near_axis, true))
+        } else if t_far >= ray.t_min && t_far <= ray.t_max {
+            Some((t_far, far_axis, false))
+        } else {
+            None  // Fuera del intervalo del rayo (p. ej. detrás de la cámara)
         }
+    }
+}
 
-        if (t_min > ty_max) || (ty_min > t_max) {
-            return Intersect::empty();  // No hay intersección
-        }
+impl RayIntersect for Cube {
+    fn ray_intersect(&self, ray: &Ray) -> Option<Hit<'_>> {
+        let (distance, axis, front_face) = self.slab_intersect(ray)?;
 
-        if ty_min > t_min {
-            t_min = ty_min;
-        }
-        if ty_max < t_max {
-            t_max = ty_max;
-        }
+        // La normal siempre apunta hacia afuera del cubo: contra el rayo al entrar, a favor al salir
+        let mut normal = Vec3::zeros();
+        let sign = ray.direction[axis].signum();
+        normal[axis] = if front_face { -sign } else { sign };
 
-        let mut tz_min = (self.min.z - ray_origin.z) / ray_direction.z;
-        let mut tz_max = (self.max.z - ray_origin.z) / ray_direction.z;
+        let point = ray.at(distance);
 
-        if tz_min > tz_max {
-            std::mem::swap(&mut tz_min, &mut tz_max);
-        }
-
-        if (t_min > tz_max) || (tz_min > t_max) {
-            return Intersect::empty();  // No hay intersección
-        }
+        Some(Hit {
+            point,
+            normal,
+            distance,
+            front_face,
+            uv: Aabb::new(self.min, self.max).face_uv(&point, axis),
+            object_id: self.id,
+            material: &self.material,
+        })
+    }
 
-        if tz_min > t_min {
-            t_min = tz_min;
-        }
-        if tz_max < t_max {
-            t_max = tz_max;
+    fn occlusion(&self, ray: &Ray) -> Occlusion {
+        match self.slab_intersect(ray) {
+            Some(_) if self.material.albedo[3] > 0.0 => Occlusion::Transmissive,
+            Some(_) => Occlusion::Opaque,
+            None => Occlusion::None,
         }
+    }
 
-        let intersection_distance = t_min;
-        if intersection_distance < 0.0 {
-            return Intersect::empty();  // La intersección está detrás de la cámara
+    fn update(&mut self, time: f32) {
+        if let Some(animation) = &self.material_animation {
+            animation.apply(Arc::make_mut(&mut self.material), time);
         }
+    }
 
-        let intersect_point = ray_origin + ray_direction * intersection_distance;
-        let normal = self.compute_normal(&intersect_point);
-
-        // Convierte el punto de intersección a coordenadas UV
-
-
-        Intersect {
-            point: intersect_point,
-            normal,
-            distance: intersection_distance,
-            material: self.material.clone(),  // Asumimos que la textura está en el material
-            is_intersecting: true,
-        }
+    fn bounds(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.min, self.max))
     }
 }
 
 impl Cube {
-    fn compute_normal(&self, point: &Vec3) -> Vec3 {
-        if (point.x - self.min.x).abs() < 1e-4 {
-            Vec3::new(-1.0, 0.0, 0.0)
-        } else if (point.x - self.max.x).abs() < 1e-4 {
-            Vec3::new(1.0, 0.0, 0.0)
-        } else if (point.y - self.min.y).abs() < 1e-4 {
-            Vec3::new(0.0, -1.0, 0.0)
-        } else if (point.y - self.max.y).abs() < 1e-4 {
-            Vec3::new(0.0, 1.0, 0.0)
-        } else if (point.z - self.min.z).abs() < 1e-4 {
-            Vec3::new(0.0, 0.0, -1.0)
-        } else {
-            Vec3::new(0.0, 0.0, 1.0)
+    // Reparte la emisión del material en luces puntuales sobre las 6 caras del cubo
+    pub fn emissive_lights(&self, samples_per_face: usize) -> Vec<Light> {
+        if !self.material.is_emissive() || samples_per_face == 0 {
+            return Vec::new();
         }
-    }
-    
-    // Convierte el punto de intersección 3D a coordenadas UV
 
+        let n = samples_per_face;
+        let intensity = self.material.emission_strength / (6 * n * n) as f32;
+        let size = self.max - self.min;
+        let mut lights = Vec::with_capacity(6 * n * n);
+
+        for axis in 0..3 {
+            let (u_axis, v_axis) = ((axis + 1) % 3, (axis + 2) % 3);
+            for side in [-1.0f32, 1.0] {
+                for i in 0..n {
+                    for j in 0..n {
+                        let mut position = self.min;
+                        position[axis] = if side < 0.0 {
+                            self.min[axis] - EMISSIVE_LIGHT_OFFSET
+                        } else {
+                            self.max[axis] + EMISSIVE_LIGHT_OFFSET
+                        };
+                        position[u_axis] += size[u_axis] * (i as f32 + 0.5) / n as f32;
+                        position[v_axis] += size[v_axis] * (j as f32 + 0.5) / n as f32;
+
+                        lights.push(
+                            Light::new(position, self.material.emission, intensity)
+                                .with_attenuation(EMISSIVE_LIGHT_ATTENUATION),
+                        );
+                    }
+                }
+            }
+        }
 
+        lights
+    }
 }
 
 // Crear tronco
-pub fn create_tronco(base: Vec3, top: Vec3, material: Material) -> Box<Cube> {
+pub fn create_tronco(base: Vec3, top: Vec3, material: Arc<Material>) -> Box<Cube> {
     Box::new(Cube::new(base, top, material))
 }
\ No newline at end of file
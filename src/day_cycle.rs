@@ -0,0 +1,58 @@
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+use crate::color::Color;
+use crate::light::Light;
+
+// Ciclo de día y noche: el sol da una vuelta completa en `cycle_duration` segundos
+pub struct DayCycle {
+    pub cycle_duration: f32,
+    pub sun_radius: f32,
+    pub day_color: Color,
+    pub night_color: Color,
+}
+
+impl DayCycle {
+    pub fn new(cycle_duration: f32) -> Self {
+        DayCycle {
+            cycle_duration,
+            sun_radius: 10.0,
+            day_color: Color::new(255, 255, 224),
+            night_color: Color::new(25, 25, 112),
+        }
+    }
+
+    // 0.0 = amanecer, 0.25 = mediodía, 0.5 = atardecer
+    pub fn day_fraction(&self, time: f32) -> f32 {
+        let time_in_cycle = time.rem_euclid(self.cycle_duration);
+        time_in_cycle / self.cycle_duration
+    }
+
+    pub fn sun_position(&self, time: f32) -> Vec3 {
+        let sun_angle = self.day_fraction(time) * 2.0 * PI;
+        let sun_x = self.sun_radius * sun_angle.cos();
+        let sun_y = self.sun_radius * sun_angle.sin();
+        let sun_z = 0.0;
+
+        Vec3::new(sun_x, sun_y, sun_z)
+    }
+
+    pub fn apply(&self, sun: &mut Light, time: f32) {
+        let day_fraction = self.day_fraction(time);
+
+        sun.position = self.sun_position(time);
+
+        sun.color = if day_fraction < 0.5 {
+            let t = day_fraction / 0.5;
+            self.day_color.interpolate(&self.night_color, t)
+        } else {
+            let t = (day_fraction - 0.5) / 0.5;
+            self.night_color.interpolate(&self.day_color, 1.0 - t)
+        };
+
+        sun.intensity = if day_fraction < 0.5 {
+            1.0 - day_fraction * 2.0
+        } else {
+            0.1
+        };
+    }
+}
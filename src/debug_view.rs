@@ -0,0 +1,146 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::aov::id_color;
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::ray_intersect::{closest_hit, take_intersection_tests};
+use crate::renderer::{RenderSettings, cast_ray, primary_ray};
+use crate::sampling::Sampler;
+use crate::scene::Scene;
+
+// Vistas de depuración: en vez de sombrear, muestran un dato del impacto primario
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugView {
+    Normals,
+    Depth,
+    Uv,
+    MaterialId,
+    ReflectionMask,
+    RefractionMask,
+    IntersectionHeatmap, // pruebas rayo-cubo por píxel, con todos los rebotes
+    AmbientOcclusion,
+}
+
+impl DebugView {
+    pub fn name(self) -> &'static str {
+        match self {
+            DebugView::Normals => "normales",
+            DebugView::Depth => "profundidad",
+            DebugView::Uv => "UV",
+            DebugView::MaterialId => "materiales",
+            DebugView::ReflectionMask => "reflexión",
+            DebugView::RefractionMask => "refracción",
+            DebugView::IntersectionHeatmap => "intersecciones",
+            DebugView::AmbientOcclusion => "oclusión ambiental",
+        }
+    }
+}
+
+// Devuelve false si `cancel` se activó antes de terminar el cuadro
+pub fn render_debug(
+    framebuffer: &mut Framebuffer,
+    scene: &Scene,
+    camera: &Camera,
+    settings: &RenderSettings,
+    view: DebugView,
+    cancel: &AtomicBool,
+) -> bool {
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    let forward = (camera.center - camera.eye).normalize();
+
+    // Profundidad y mapa de calor se normalizan con el máximo del cuadro,
+    // así que primero se juntan los valores
+    let mut values = vec![0.0_f32; width * height];
+
+    for y in 0..height {
+        if cancel.load(Ordering::Relaxed) {
+            return false;
+        }
+        for x in 0..width {
+            let ray = primary_ray(camera, x as f32, y as f32, width, height);
+            let mut sampler = Sampler::for_pixel(x, y, 0);
+            let index = y * width + x;
+
+            if view == DebugView::IntersectionHeatmap {
+                take_intersection_tests();
+                cast_ray(&ray, scene, settings, &mut sampler, 0, None);
+                values[index] = take_intersection_tests() as f32;
+                continue;
+            }
+
+            let hit = closest_hit(&scene.objects, &ray);
+            let color = match (view, &hit) {
+                (DebugView::AmbientOcclusion, Some(hit)) => {
+                    let visibility = scene.ambient.visibility(hit, settings, &scene.objects, &mut sampler);
+                    Color::new(255, 255, 255) * visibility
+                }
+                (DebugView::AmbientOcclusion, None) => Color::new(255, 255, 255),
+                (_, None) => Color::black(),
+                (DebugView::Normals, Some(hit)) => {
+                    let n = hit.normal * 0.5 + nalgebra_glm::Vec3::new(0.5, 0.5, 0.5);
+                    Color::new(to_u8(n.x), to_u8(n.y), to_u8(n.z))
+                }
+                (DebugView::Depth, Some(hit)) => {
+                    values[index] = hit.distance * ray.direction.dot(&forward);
+                    continue;
+                }
+                (DebugView::Uv, Some(hit)) => Color::new(to_u8(hit.uv.0), to_u8(hit.uv.1), 0),
+                (DebugView::MaterialId, Some(hit)) => {
+                    let [r, g, b] = id_color(hit.material.id);
+                    Color::new(r, g, b)
+                }
+                (DebugView::ReflectionMask, Some(hit)) => Color::new(255, 255, 255) * hit.material.albedo[2],
+                (DebugView::RefractionMask, Some(hit)) => Color::new(255, 255, 255) * hit.material.albedo[3],
+                (DebugView::IntersectionHeatmap, Some(_)) => unreachable!(),
+            };
+
+            framebuffer.point(x, y, color);
+        }
+    }
+
+    let max = values.iter().copied().fold(0.0_f32, f32::max);
+    match view {
+        // Lo más cercano en blanco, lo más lejano en gris oscuro; el cielo en negro
+        DebugView::Depth => {
+            let min = values.iter().copied().filter(|&depth| depth > 0.0).fold(max, f32::min);
+            fill(framebuffer, &values, |depth| {
+                if depth > 0.0 {
+                    Color::new(255, 255, 255) * (1.0 - 0.8 * (depth - min) / (max - min).max(1e-6))
+                } else {
+                    Color::black()
+                }
+            })
+        }
+        // Escala logarítmica: la mayoría de los píxeles hace pocas pruebas
+        DebugView::IntersectionHeatmap => fill(framebuffer, &values, |tests| {
+            heat_color((1.0 + tests).ln() / (1.0 + max).ln().max(1e-6))
+        }),
+        _ => {}
+    }
+    true
+}
+
+fn fill(framebuffer: &mut Framebuffer, values: &[f32], color: impl Fn(f32) -> Color) {
+    for (pixel, &value) in framebuffer.buffer.iter_mut().zip(values) {
+        *pixel = color(value).to_hex();
+    }
+}
+
+fn to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+// Rampa azul → verde → amarillo → rojo para t en [0, 1]
+fn heat_color(t: f32) -> Color {
+    let stops = [
+        Color::new(0, 0, 96),
+        Color::new(0, 160, 255),
+        Color::new(0, 220, 0),
+        Color::new(255, 230, 0),
+        Color::new(255, 0, 0),
+    ];
+    let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let i = (scaled as usize).min(stops.len() - 2);
+    stops[i].interpolate(&stops[i + 1], scaled - i as f32)
+}
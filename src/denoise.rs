@@ -0,0 +1,154 @@
+use crate::aov::Aov;
+use crate::framebuffer::Framebuffer;
+
+// Núcleo B3-spline de 5 taps del filtro à-trous
+const KERNEL: [f32; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+// Filtro à-trous que respeta los bordes: promedia píxeles vecinos solo si se
+// parecen en color, normal, albedo y profundidad. Cada pasada duplica la
+// separación entre taps, así que el radio crece sin aumentar el costo.
+#[derive(Debug, Clone, Copy)]
+pub struct Denoiser {
+    pub strength: f32,     // tolerancia de color; 0 deja la imagen intacta
+    pub iterations: u32,
+    pub normal_sigma: f32, // tolerancia entre normales (1 - coseno)
+    pub albedo_sigma: f32,
+    pub depth_sigma: f32,  // diferencia de profundidad relativa
+}
+
+impl Denoiser {
+    // Buffers que el render debe llenar para guiar el filtro
+    pub const GUIDES: [Aov; 3] = [Aov::Normal, Aov::Albedo, Aov::Depth];
+
+    pub fn new(strength: f32) -> Self {
+        Denoiser {
+            strength,
+            iterations: 3,
+            normal_sigma: 0.1,
+            albedo_sigma: 0.1,
+            depth_sigma: 0.05,
+        }
+    }
+
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    // Filtra el color del framebuffer en su lugar. Sin los buffers guía no hace nada.
+    pub fn apply(&self, framebuffer: &mut Framebuffer) {
+        if self.strength <= 0.0 {
+            return;
+        }
+        let Some(aovs) = framebuffer.aovs.as_ref() else {
+            return;
+        };
+        let (Some(normals), Some(albedo), Some(depth)) =
+            (aovs.layer(Aov::Normal), aovs.layer(Aov::Albedo), aovs.layer(Aov::Depth))
+        else {
+            return;
+        };
+
+        let (width, height) = (framebuffer.width, framebuffer.height);
+        let mut color: Vec<[f32; 3]> = framebuffer.buffer.iter().map(|&pixel| unpack(pixel)).collect();
+        let mut filtered = color.clone();
+        let mut color_sigma = self.strength * 0.1;
+
+        for iteration in 0..self.iterations {
+            let step = 1_isize << iteration;
+
+            for y in 0..height {
+                for x in 0..width {
+                    let p = y * width + x;
+                    let normal_p = vec3_at(normals, p);
+                    let albedo_p = vec3_at(albedo, p);
+
+                    let mut sum = [0.0; 3];
+                    let mut weight_sum = 0.0;
+
+                    for (j, ky) in KERNEL.iter().enumerate() {
+                        let qy = y as isize + (j as isize - 2) * step;
+                        if qy < 0 || qy >= height as isize {
+                            continue;
+                        }
+                        for (i, kx) in KERNEL.iter().enumerate() {
+                            let qx = x as isize + (i as isize - 2) * step;
+                            if qx < 0 || qx >= width as isize {
+                                continue;
+                            }
+                            let q = qy as usize * width + qx as usize;
+
+                            let Some(depth_distance) = relative_depth(depth[p], depth[q]) else {
+                                continue;
+                            };
+                            // El cielo no tiene normal ni albedo: solo cuenta el color
+                            let geometry = if depth[p].is_finite() {
+                                let normal_distance = 1.0 - dot(normal_p, vec3_at(normals, q)).clamp(-1.0, 1.0);
+                                gaussian(normal_distance * normal_distance, self.normal_sigma)
+                                    * gaussian(distance_squared(albedo_p, vec3_at(albedo, q)), self.albedo_sigma)
+                                    * gaussian(depth_distance * depth_distance, self.depth_sigma * step as f32)
+                            } else {
+                                1.0
+                            };
+                            let weight = kx * ky
+                                * gaussian(distance_squared(color[p], color[q]), color_sigma)
+                                * geometry;
+
+                            for c in 0..3 {
+                                sum[c] += color[q][c] * weight;
+                            }
+                            weight_sum += weight;
+                        }
+                    }
+
+                    // El píxel central siempre aporta, así que weight_sum > 0
+                    filtered[p] = sum.map(|v| v / weight_sum);
+                }
+            }
+
+            std::mem::swap(&mut color, &mut filtered);
+            // Las pasadas gruesas solo deben suavizar el ruido que quedó
+            color_sigma *= 0.5;
+        }
+
+        for (pixel, c) in framebuffer.buffer.iter_mut().zip(&color) {
+            *pixel = pack(*c);
+        }
+    }
+}
+
+fn unpack(pixel: u32) -> [f32; 3] {
+    [(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8].map(|c| c as f32 / 255.0)
+}
+
+fn pack(color: [f32; 3]) -> u32 {
+    let [r, g, b] = color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u32);
+    (r << 16) | (g << 8) | b
+}
+
+fn vec3_at(data: &[f32], index: usize) -> [f32; 3] {
+    [data[index * 3], data[index * 3 + 1], data[index * 3 + 2]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn distance_squared(a: [f32; 3], b: [f32; 3]) -> f32 {
+    let d = [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+    dot(d, d)
+}
+
+fn gaussian(distance_squared: f32, sigma: f32) -> f32 {
+    (-distance_squared / (sigma * sigma).max(1e-12)).exp()
+}
+
+// Diferencia de profundidad relativa al píxel central. El cielo (profundidad
+// infinita) solo se mezcla con cielo.
+fn relative_depth(center: f32, other: f32) -> Option<f32> {
+    match (center.is_finite(), other.is_finite()) {
+        (true, true) => Some((center - other).abs() / center.max(1e-4)),
+        (false, false) => Some(0.0),
+        _ => None,
+    }
+}
@@ -0,0 +1,254 @@
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+
+use crate::framebuffer::Framebuffer;
+use crate::renderer::{RenderSettings, Renderer};
+use crate::scene::Scene;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    // Un PNG numerado por cuadro: `<salida>_0000.png` si la salida termina en
+    // .png, si no `frame_0000.png` dentro de la carpeta de salida
+    PngSequence,
+    Gif,
+    Apng,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AovFormat {
+    // Un PNG por AOV y cuadro, remapeado para verse
+    Png,
+    // Un EXR de 32 bits por cuadro con todas las capas
+    Exr,
+}
+
+// Qué cuadros se exportan y a dónde. El tamaño, las AOVs y el denoiser son
+// parte de RenderSettings; las opciones se leen en Config.
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub output: PathBuf,
+    pub format: ExportFormat,
+    pub fps: u32,
+    pub start_frame: u32,
+    pub end_frame: u32, // exclusivo
+    pub follow_camera_path: bool,
+    pub aov_format: AovFormat,
+}
+
+impl ExportOptions {
+    pub fn frame_count(&self) -> u32 {
+        self.end_frame.saturating_sub(self.start_frame)
+    }
+
+    // Config ya revisa lo que viene de la línea de comandos; esto cubre a quien
+    // arme las opciones a mano
+    fn validate(&self) -> Result<(), String> {
+        if self.fps == 0 {
+            return Err("los fps tienen que ser mayores que cero".to_string());
+        }
+        if self.end_frame <= self.start_frame {
+            return Err(format!("rango de cuadros vacío: {}..{}", self.start_frame, self.end_frame));
+        }
+        Ok(())
+    }
+
+    // La salida es una carpeta solo para una secuencia de PNGs sin extensión .png
+    fn output_is_dir(&self) -> bool {
+        self.format == ExportFormat::PngSequence && !has_png_extension(&self.output)
+    }
+
+    // Carpeta y prefijo de los cuadros de una secuencia de PNGs
+    fn sequence_target(&self) -> (PathBuf, String) {
+        if self.output_is_dir() {
+            return (self.output.clone(), "frame_".to_string());
+        }
+        let dir = self.output.parent().map_or_else(PathBuf::new, Path::to_path_buf);
+        (dir, format!("{}_", self.stem()))
+    }
+
+    fn stem(&self) -> &str {
+        self.output.file_stem().and_then(|s| s.to_str()).unwrap_or("export")
+    }
+
+    // Las AOVs van dentro de la carpeta de la secuencia de PNGs, o en
+    // `<nombre>_aovs/` al lado de la salida
+    fn aov_dir(&self) -> PathBuf {
+        if self.output_is_dir() {
+            self.output.clone()
+        } else {
+            self.output.with_file_name(format!("{}_aovs", self.stem()))
+        }
+    }
+
+    // Solo si se pidieron; las guías del denoiser no se escriben por sí solas
+    fn write_aovs(&self, settings: &RenderSettings, framebuffer: &Framebuffer, frame: u32) -> Result<(), String> {
+        let Some(aovs) = framebuffer.aovs.as_ref().filter(|_| !settings.aovs.is_empty()) else {
+            return Ok(());
+        };
+        let dir = self.aov_dir();
+        match self.aov_format {
+            AovFormat::Png => aovs.save_images(&dir, &format!("frame_{:04}_", frame)),
+            AovFormat::Exr => {
+                fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+                aovs.save_exr(&dir.join(format!("frame_{:04}.exr", frame)), &framebuffer.buffer)
+            }
+        }
+    }
+}
+
+// Destino de los cuadros; GIF y APNG se escriben a medida que se renderizan
+enum FrameSink {
+    PngSequence(PathBuf, String), // carpeta y prefijo
+    Gif(GifEncoder<BufWriter<File>>, Delay),
+    Apng(png::Writer<BufWriter<File>>),
+}
+
+impl FrameSink {
+    fn create(options: &ExportOptions, settings: &RenderSettings) -> Result<Self, String> {
+        match options.format {
+            ExportFormat::PngSequence => {
+                let (dir, prefix) = options.sequence_target();
+                if !dir.as_os_str().is_empty() {
+                    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+                }
+                Ok(FrameSink::PngSequence(dir, prefix))
+            }
+            ExportFormat::Gif => {
+                let mut encoder = GifEncoder::new_with_speed(create_file(&options.output)?, 10);
+                encoder.set_repeat(Repeat::Infinite).map_err(|e| e.to_string())?;
+                let delay = Delay::from_numer_denom_ms(1000, options.fps);
+                Ok(FrameSink::Gif(encoder, delay))
+            }
+            ExportFormat::Apng => {
+                let mut encoder = png::Encoder::new(
+                    create_file(&options.output)?,
+                    settings.width as u32,
+                    settings.height as u32,
+                );
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder.set_animated(options.frame_count(), 0).map_err(|e| e.to_string())?;
+                let fps = u16::try_from(options.fps).map_err(|_| "fps demasiado alto para APNG".to_string())?;
+                encoder.set_frame_delay(1, fps).map_err(|e| e.to_string())?;
+                Ok(FrameSink::Apng(encoder.write_header().map_err(|e| e.to_string())?))
+            }
+        }
+    }
+
+    fn write(&mut self, frame: u32, image: RgbaImage) -> Result<(), String> {
+        match self {
+            FrameSink::PngSequence(dir, prefix) => image
+                .save(dir.join(format!("{}{:04}.png", prefix, frame)))
+                .map_err(|e| e.to_string()),
+            FrameSink::Gif(encoder, delay) => encoder
+                .encode_frame(Frame::from_parts(image, 0, 0, *delay))
+                .map_err(|e| e.to_string()),
+            FrameSink::Apng(writer) => writer.write_image_data(&image).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self {
+            FrameSink::Apng(writer) => writer.finish().map_err(|e| e.to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn has_png_extension(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("png"))
+}
+
+fn create_file(path: &Path) -> Result<BufWriter<File>, String> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    File::create(path)
+        .map(BufWriter::new)
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Renderiza la animación cuadro por cuadro. El tiempo de cada cuadro sale de su
+// número, sin depender de cuánto tarde ni acumular error en exportaciones largas.
+// `progress` recibe (cuadros listos, total) después de cada uno.
+pub fn export_animation(
+    scene: &mut Scene,
+    settings: &RenderSettings,
+    options: &ExportOptions,
+    mut progress: impl FnMut(u32, u32),
+) -> Result<(), String> {
+    options.validate()?;
+    if options.follow_camera_path && scene.camera_path.is_none() {
+        return Err("la escena no tiene recorrido de cámara".to_string());
+    }
+
+    let mut renderer = Renderer::new(settings.clone());
+    let mut sink = FrameSink::create(options, settings)?;
+
+    for frame in options.start_frame..options.end_frame {
+        let time = frame as f32 / options.fps as f32;
+        scene.set_time(time);
+        let camera = match scene.camera_path.as_ref().filter(|_| options.follow_camera_path) {
+            Some(path) => path.camera_at(time),
+            None => scene.camera.clone(),
+        };
+
+        let framebuffer = renderer.render(scene, &camera);
+        sink.write(frame, framebuffer.to_rgba_image())?;
+        options.write_aovs(settings, framebuffer, frame)?;
+        progress(frame - options.start_frame + 1, options.frame_count());
+    }
+
+    sink.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rechaza_fps_nulos_y_rangos_vacios() {
+        let options = ExportOptions {
+            output: std::env::temp_dir().join(format!("proyecto2raytracing_{}_no_se_escribe", std::process::id())),
+            format: ExportFormat::PngSequence,
+            fps: 0,
+            start_frame: 0,
+            end_frame: 10,
+            follow_camera_path: false,
+            aov_format: AovFormat::Png,
+        };
+        let settings = RenderSettings::default();
+        let mut scene = Scene::empty();
+        assert!(export_animation(&mut scene, &settings, &options, |_, _| ()).is_err());
+
+        let backwards = ExportOptions { fps: 30, start_frame: 10, end_frame: 5, ..options };
+        assert_eq!(backwards.frame_count(), 0);
+        assert!(export_animation(&mut scene, &settings, &backwards, |_, _| ()).is_err());
+        assert!(!backwards.output.exists());
+    }
+
+    #[test]
+    fn una_salida_png_es_una_secuencia_numerada() {
+        let dir = std::env::temp_dir().join(format!("proyecto2raytracing_{}_secuencia", std::process::id()));
+        let options = ExportOptions {
+            output: dir.join("cuadros.png"),
+            format: ExportFormat::PngSequence,
+            fps: 30,
+            start_frame: 3,
+            end_frame: 5,
+            follow_camera_path: false,
+            aov_format: AovFormat::Png,
+        };
+        let settings = RenderSettings { width: 4, height: 3, ..RenderSettings::default() };
+        let result = export_animation(&mut Scene::empty(), &settings, &options, |_, _| ());
+        let written = [3, 4].map(|frame| dir.join(format!("cuadros_{:04}.png", frame)).is_file());
+        let _ = fs::remove_dir_all(&dir);
+
+        result.unwrap();
+        assert_eq!(written, [true, true]);
+    }
+}
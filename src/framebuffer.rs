@@ -1,10 +1,35 @@
+use image::{Rgba, RgbaImage};
+
+use crate::aov::{Aov, AovBuffers};
+use crate::color::Color;
+use crate::font::{self, ADVANCE, GLYPH_HEIGHT};
+use crate::texture::Texture;
+
+// Color con opacidad para las primitivas 2D. Un Color solo se dibuja opaco.
+#[derive(Debug, Clone, Copy)]
+pub struct Paint {
+    pub color: Color,
+    pub alpha: f32,
+}
+
+impl Paint {
+    pub fn new(color: Color, alpha: f32) -> Self {
+        Paint { color, alpha: alpha.clamp(0.0, 1.0) }
+    }
+}
+
+impl From<Color> for Paint {
+    fn from(color: Color) -> Self {
+        Paint { color, alpha: 1.0 }
+    }
+}
 
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
     pub buffer: Vec<u32>,
+    pub aovs: Option<AovBuffers>, // buffers auxiliares, solo si se pidieron
     background_color: u32,
-    current_color: u32,
 }
 
 impl Framebuffer {
@@ -14,19 +39,215 @@ impl Framebuffer {
             height,
             buffer: vec![0; width * height],
             background_color: 0x000000,
-            current_color: 0xFFFFFF
+            aovs: None,
         }
     }
 
+    pub fn enable_aovs(&mut self, aovs: &[Aov]) {
+        self.aovs = Some(AovBuffers::new(self.width, self.height, aovs));
+    }
+
     pub fn clear(&mut self) {
         for pixel in self.buffer.iter_mut() {
             *pixel = self.background_color;
         }
     }
 
-    pub fn point(&mut self, x: usize, y: usize) {
+    pub fn point(&mut self, x: usize, y: usize, color: Color) {
         if x < self.width && y < self.height {
-            self.buffer[y * self.width + x] = self.current_color;
+            self.buffer[y * self.width + x] = color.to_hex();
+        }
+    }
+
+    // Punto con coordenadas con signo y mezcla alfa; lo que cae fuera se descarta
+    pub fn plot(&mut self, x: isize, y: isize, paint: impl Into<Paint>) {
+        let paint = paint.into();
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height || paint.alpha <= 0.0 {
+            return;
+        }
+        let pixel = &mut self.buffer[y as usize * self.width + x as usize];
+        *pixel = if paint.alpha >= 1.0 {
+            paint.color.to_hex()
+        } else {
+            Color::from_hex(*pixel).interpolate(&paint.color, paint.alpha).to_hex()
+        };
+    }
+
+    pub fn fill_rect(&mut self, x: isize, y: isize, width: usize, height: usize, paint: impl Into<Paint>) {
+        let paint = paint.into();
+        let x_start = x.max(0) as usize;
+        let y_start = y.max(0) as usize;
+        let x_end = (x + width as isize).clamp(0, self.width as isize) as usize;
+        let y_end = (y + height as isize).clamp(0, self.height as isize) as usize;
+
+        for row in y_start..y_end {
+            if paint.alpha >= 1.0 {
+                let start = row * self.width;
+                if x_start < x_end {
+                    self.buffer[start + x_start..start + x_end].fill(paint.color.to_hex());
+                }
+            } else {
+                for column in x_start..x_end {
+                    self.plot(column as isize, row as isize, paint);
+                }
+            }
+        }
+    }
+
+    // Contorno de un rectángulo de un píxel de grosor
+    pub fn rect(&mut self, x: isize, y: isize, width: usize, height: usize, paint: impl Into<Paint>) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let paint = paint.into();
+        let (right, bottom) = (x + width as isize - 1, y + height as isize - 1);
+        self.fill_rect(x, y, width, 1, paint);
+        if height > 1 {
+            self.fill_rect(x, bottom, width, 1, paint);
+        }
+        if height > 2 {
+            self.fill_rect(x, y + 1, 1, height - 2, paint);
+            if width > 1 {
+                self.fill_rect(right, y + 1, 1, height - 2, paint);
+            }
+        }
+    }
+
+    // Línea de Bresenham entre dos puntos. Primero se recorta a la pantalla
+    // (Liang-Barsky), así los extremos pueden estar muy lejos sin costo.
+    pub fn line(&mut self, x0: isize, y0: isize, x1: isize, y1: isize, paint: impl Into<Paint>) {
+        let paint = paint.into();
+        let Some((x0, y0, x1, y1)) = self.clip_line(x0, y0, x1, y1) else {
+            return;
+        };
+
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let step_x = if x0 < x1 { 1 } else { -1 };
+        let step_y = if y0 < y1 { 1 } else { -1 };
+        let mut error = dx + dy;
+        let (mut x, mut y) = (x0, y0);
+
+        loop {
+            self.plot(x, y, paint);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    fn clip_line(&self, x0: isize, y0: isize, x1: isize, y1: isize) -> Option<(isize, isize, isize, isize)> {
+        let (max_x, max_y) = (self.width as f32 - 1.0, self.height as f32 - 1.0);
+        let (dx, dy) = ((x1 - x0) as f32, (y1 - y0) as f32);
+        let (mut t0, mut t1) = (0.0_f32, 1.0_f32);
+
+        for (p, q) in [
+            (-dx, x0 as f32),
+            (dx, max_x - x0 as f32),
+            (-dy, y0 as f32),
+            (dy, max_y - y0 as f32),
+        ] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
+                }
+            } else {
+                let t = q / p;
+                if p < 0.0 {
+                    t0 = t0.max(t);
+                } else {
+                    t1 = t1.min(t);
+                }
+            }
+        }
+
+        if t0 > t1 {
+            return None;
+        }
+        let at = |t: f32| ((x0 as f32 + t * dx).round() as isize, (y0 as f32 + t * dy).round() as isize);
+        let (ax, ay) = at(t0);
+        let (bx, by) = at(t1);
+        Some((ax, ay, bx, by))
+    }
+
+    // Circunferencia por el algoritmo del punto medio
+    pub fn circle(&mut self, cx: isize, cy: isize, radius: usize, paint: impl Into<Paint>) {
+        let paint = paint.into();
+        let mut x = radius as isize;
+        let mut y = 0;
+        let mut error = 1 - x;
+
+        while x >= y {
+            // Los octantes se repiten en los ejes y en las diagonales; se dibuja
+            // cada punto una sola vez para que la mezcla alfa no se acumule
+            let mut points = vec![
+                (x, y), (y, x), (-y, x), (-x, y),
+                (-x, -y), (-y, -x), (y, -x), (x, -y),
+            ];
+            points.sort_unstable();
+            points.dedup();
+            for (px, py) in points {
+                self.plot(cx + px, cy + py, paint);
+            }
+
+            y += 1;
+            if error < 0 {
+                error += 2 * y + 1;
+            } else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    pub fn fill_circle(&mut self, cx: isize, cy: isize, radius: usize, paint: impl Into<Paint>) {
+        let paint = paint.into();
+        let radius = radius as isize;
+        for dy in -radius..=radius {
+            let half_width = ((radius * radius - dy * dy) as f32).sqrt().round() as isize;
+            self.fill_rect(cx - half_width, cy + dy, (2 * half_width + 1) as usize, 1, paint);
+        }
+    }
+
+    // Copia la textura escalada a width x height, respetando su canal alfa
+    pub fn blit(&mut self, texture: &Texture, x: isize, y: isize, width: usize, height: usize, alpha: f32) {
+        for row in 0..height {
+            for column in 0..width {
+                let u = (column as f32 + 0.5) / width as f32;
+                let v = 1.0 - (row as f32 + 0.5) / height as f32;
+                let [r, g, b, a] = texture.get_color_at((u, v));
+                let paint = Paint::new(Color::new(r, g, b), alpha * a as f32 / 255.0);
+                self.plot(x + column as isize, y + row as isize, paint);
+            }
+        }
+    }
+
+    // Texto con la fuente de mapa de bits; cada píxel del glifo ocupa scale x scale.
+    // Los saltos de línea bajan a la siguiente fila.
+    pub fn text(&mut self, x: isize, y: isize, text: &str, scale: usize, paint: impl Into<Paint>) {
+        let paint = paint.into();
+        let step = scale as isize;
+        for (row, line) in text.lines().enumerate() {
+            let line_y = y + (row * font::LINE_HEIGHT) as isize * step;
+            for (i, c) in line.chars().enumerate() {
+                let glyph_x = x + (i * ADVANCE) as isize * step;
+                for (column, bits) in font::glyph(c).iter().enumerate() {
+                    for bit in 0..GLYPH_HEIGHT {
+                        if bits & (1 << bit) != 0 {
+                            self.fill_rect(glyph_x + column as isize * step, line_y + bit as isize * step, scale, scale, paint);
+                        }
+                    }
+                }
+            }
         }
     }
 
@@ -34,7 +255,10 @@ impl Framebuffer {
         self.background_color = color;
     }
 
-    pub fn set_current_color(&mut self, color: u32) {
-        self.current_color = color;
+    pub fn to_rgba_image(&self) -> RgbaImage {
+        RgbaImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let pixel = self.buffer[y as usize * self.width + x as usize];
+            Rgba([(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8, 255])
+        })
     }
 }
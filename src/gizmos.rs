@@ -0,0 +1,73 @@
+use nalgebra_glm::Vec3;
+
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::framebuffer::{Framebuffer, Paint};
+use crate::scene::Scene;
+
+const AXIS_LENGTH: f32 = 0.5;
+const BOUNDS_COLOR: Color = Color::new(255, 220, 60);
+const BOUNDS_ALPHA: f32 = 0.6;
+const LIGHT_MARKER_RADIUS: usize = 4;
+// Distancia mínima delante de la cámara; los segmentos se recortan contra este plano
+const NEAR_PLANE: f32 = 1e-2;
+
+// Ayudas visuales dibujadas sobre el render: ejes del mundo, cajas envolventes
+// de los objetos y la posición de cada luz
+pub fn draw_gizmos(framebuffer: &mut Framebuffer, scene: &Scene, camera: &Camera) {
+    for object in &scene.objects {
+        if let Some(bounds) = object.bounds() {
+            let corners = bounds.corners();
+            for (a, b) in BOX_EDGES {
+                segment(framebuffer, camera, &corners[a], &corners[b], Paint::new(BOUNDS_COLOR, BOUNDS_ALPHA));
+            }
+        }
+    }
+
+    let origin = Vec3::zeros();
+    segment(framebuffer, camera, &origin, &Vec3::new(AXIS_LENGTH, 0.0, 0.0), Color::new(230, 40, 40));
+    segment(framebuffer, camera, &origin, &Vec3::new(0.0, AXIS_LENGTH, 0.0), Color::new(40, 200, 40));
+    segment(framebuffer, camera, &origin, &Vec3::new(0.0, 0.0, AXIS_LENGTH), Color::new(50, 90, 255));
+
+    for light in &scene.lights {
+        if let Some((x, y)) = camera.project(&light.position, framebuffer.width, framebuffer.height) {
+            let (x, y) = (x.round() as isize, y.round() as isize);
+            framebuffer.fill_circle(x, y, LIGHT_MARKER_RADIUS, light.color);
+            framebuffer.circle(x, y, LIGHT_MARKER_RADIUS + 1, Color::new(255, 255, 255));
+        }
+    }
+}
+
+// Índices de las esquinas de Aabb::corners unidas por cada arista
+const BOX_EDGES: [(usize, usize); 12] = [
+    (0, 1), (1, 2), (2, 3), (3, 0),
+    (4, 5), (5, 6), (6, 7), (7, 4),
+    (0, 4), (1, 5), (2, 6), (3, 7),
+];
+
+// Segmento 3D proyectado; la parte detrás de la cámara se descarta
+fn segment(framebuffer: &mut Framebuffer, camera: &Camera, a: &Vec3, b: &Vec3, paint: impl Into<Paint>) {
+    let (_, _, forward) = camera.basis();
+    let depth_a = (a - camera.eye).dot(&forward) - NEAR_PLANE;
+    let depth_b = (b - camera.eye).dot(&forward) - NEAR_PLANE;
+    if depth_a <= 0.0 && depth_b <= 0.0 {
+        return;
+    }
+
+    let clip = |behind: &Vec3, front: &Vec3, depth_behind: f32, depth_front: f32| {
+        let t = depth_front / (depth_front - depth_behind);
+        front + (behind - front) * t
+    };
+    let (a, b) = if depth_a <= 0.0 {
+        (clip(a, b, depth_a, depth_b), *b)
+    } else if depth_b <= 0.0 {
+        (*a, clip(b, a, depth_b, depth_a))
+    } else {
+        (*a, *b)
+    };
+
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    if let (Some((x0, y0)), Some((x1, y1))) = (camera.project(&a, width, height), camera.project(&b, width, height)) {
+        framebuffer.line(x0.round() as isize, y0.round() as isize, x1.round() as isize, y1.round() as isize, paint);
+    }
+}
@@ -0,0 +1,425 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use gltf::camera::Projection;
+use gltf::image::Format;
+use gltf::khr_lights_punctual::Kind;
+use gltf::mesh::Mode;
+use image::RgbaImage;
+use nalgebra_glm::{Mat3, Mat4, Vec3, Vec4};
+
+use crate::assets::Assets;
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::group::{Group, invert};
+use crate::light::Light;
+use crate::material::Material;
+use crate::mesh::{Mesh, Triangle};
+use crate::ray_intersect::RayIntersect;
+use crate::texture::Texture;
+
+// Acá no hay luces direccionales: se aproximan con una luz puntual sin atenuación
+// así de lejos, en sentido contrario al que alumbran. A esa distancia los rayos de
+// sombra de una escena de unas decenas de metros son casi paralelos.
+const DIRECTIONAL_DISTANCE: f32 = 1000.0;
+// glTF da las luces en unidades fotométricas: lux las direccionales, candelas las
+// puntuales y los focos. Esta iluminancia equivale a intensidad 1, la del sol de
+// las escenas; es 1 W/m² a 683 lm/W, el sol de fuerza 1 de Blender.
+const REFERENCE_LUX: f32 = 683.0;
+
+// Lo que trae un archivo glTF. La jerarquía de nodos queda como grupos anidados;
+// las luces y cámaras ya están en coordenadas del mundo.
+pub struct GltfScene {
+    pub root: Group,
+    pub lights: Vec<Light>,
+    pub cameras: Vec<Camera>,
+    pub files: Vec<PathBuf>, // .bin e imágenes externas, para vigilarlas
+}
+
+// Carga un .gltf o .glb. Los materiales se registran en `assets` como
+// "<ruta>#<índice>" o "<ruta>#<índice>:<nombre>", porque los nombres pueden repetirse.
+pub fn load(path: &Path, assets: &mut Assets) -> Result<GltfScene, String> {
+    let (document, buffers, images) = gltf::import(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let materials = document
+        .materials()
+        .enumerate()
+        .map(|(index, material)| {
+            let key = match material.name() {
+                Some(name) => format!("{}#{}:{}", path.display(), index, name),
+                None => format!("{}#{}", path.display(), index),
+            };
+            assets.imported_material(&key, || translate_material(&material, &images))
+        })
+        .collect();
+    // Sin material, glTF usa uno blanco y difuso
+    let default_material = assets.imported_material(&format!("{}#default", path.display()), || {
+        Material::new(Color::new(255, 255, 255), 10.0, [0.9, 0.2, 0.0, 0.0], 1.0, None)
+    });
+
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .ok_or_else(|| format!("{}: el archivo no tiene escenas", path.display()))?;
+
+    let mut import = Import { buffers: &buffers, materials, default_material, lights: Vec::new(), cameras: Vec::new() };
+    let elements = scene
+        .nodes()
+        .filter_map(|node| import.node(&node, &Mat4::identity()).transpose())
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let buffer_uris = document.buffers().filter_map(|buffer| match buffer.source() {
+        gltf::buffer::Source::Uri(uri) => Some(uri),
+        gltf::buffer::Source::Bin => None,
+    });
+    let image_uris = document.images().filter_map(|image| match image.source() {
+        gltf::image::Source::Uri { uri, .. } => Some(uri),
+        gltf::image::Source::View { .. } => None,
+    });
+    let files = buffer_uris
+        .chain(image_uris)
+        .filter(|uri| !uri.starts_with("data:"))
+        .map(|uri| dir.join(uri))
+        .collect();
+
+    Ok(GltfScene {
+        root: Group::new(elements, Vec3::zeros()),
+        lights: import.lights,
+        cameras: import.cameras,
+        files,
+    })
+}
+
+// Estado del recorrido de los nodos
+struct Import<'a> {
+    buffers: &'a [gltf::buffer::Data],
+    materials: Vec<Arc<Material>>,
+    default_material: Arc<Material>,
+    lights: Vec<Light>,
+    cameras: Vec<Camera>,
+}
+
+impl Import<'_> {
+    // Un nodo es un grupo con su transformación local; `parent` es la del mundo
+    // de su padre, para ubicar luces y cámaras. glTF permite escala 0 para
+    // esconder un nodo: ese nodo y sus hijos, con sus luces y cámaras, se saltean.
+    fn node(&mut self, node: &gltf::Node, parent: &Mat4) -> Result<Option<Box<dyn RayIntersect>>, String> {
+        let local = Mat4::from(node.transform().matrix());
+        let linear = Mat3::from_fn(|row, column| local[(row, column)]);
+        if invert(&linear).is_none() {
+            return Ok(None);
+        }
+        let world = parent * local;
+        let position = (world * Vec4::new(0.0, 0.0, 0.0, 1.0)).xyz();
+        // Cámaras y luces miran hacia -Z local
+        let forward = (world * Vec4::new(0.0, 0.0, -1.0, 0.0)).xyz().normalize();
+
+        // Las cámaras ortográficas no tienen equivalente y se ignoran
+        if let Some(camera) = node.camera() {
+            if let Projection::Perspective(perspective) = camera.projection() {
+                let up = (world * Vec4::new(0.0, 1.0, 0.0, 0.0)).xyz().normalize();
+                self.cameras.push(Camera::new(position, position + forward, up).with_fov(perspective.yfov()));
+            }
+        }
+
+        // Las puntuales caen con el cuadrado de la distancia en metros: I / (1 + d²)
+        // es eso lejos de la luz y no se dispara cerca. `range` se ignora.
+        if let Some(light) = node.light() {
+            let color = linear_color(light.color());
+            let intensity = light.intensity() / REFERENCE_LUX;
+            self.lights.push(match light.kind() {
+                Kind::Directional => Light::new(position - forward * DIRECTIONAL_DISTANCE, color, intensity),
+                Kind::Point => Light::new(position, color, intensity).with_attenuation(1.0),
+                Kind::Spot { inner_cone_angle, outer_cone_angle } => Light::new(position, color, intensity)
+                    .with_attenuation(1.0)
+                    .with_spot(forward, inner_cone_angle, outer_cone_angle),
+            });
+        }
+
+        let mut elements = Vec::new();
+        if let Some(mesh) = node.mesh() {
+            for primitive in mesh.primitives() {
+                if let Some(mesh) = self.primitive(&primitive)? {
+                    elements.push(Box::new(mesh) as Box<dyn RayIntersect>);
+                }
+            }
+        }
+        for child in node.children() {
+            elements.extend(self.node(&child, &world)?);
+        }
+
+        let translation = local.column(3).xyz();
+        let group = Group::new(elements, translation).with_transform(linear)?;
+        Ok(Some(Box::new(group)))
+    }
+
+    // Solo se importan listas de triángulos; puntos, líneas, tiras y abanicos se saltean
+    fn primitive(&self, primitive: &gltf::Primitive) -> Result<Option<Mesh>, String> {
+        if primitive.mode() != Mode::Triangles {
+            return Ok(None);
+        }
+        let reader = primitive.reader(|buffer| self.buffers.get(buffer.index()).map(|data| &data[..]));
+        let Some(positions) = reader.read_positions() else {
+            return Ok(None);
+        };
+        let positions: Vec<Vec3> = positions.map(Vec3::from).collect();
+        let normals: Option<Vec<Vec3>> = reader.read_normals().map(|normals| normals.map(Vec3::from).collect());
+        // glTF tiene el origen de las UV arriba a la izquierda; las texturas de acá, abajo
+        let uvs: Option<Vec<(f32, f32)>> = reader
+            .read_tex_coords(0)
+            .map(|uvs| uvs.into_f32().map(|[u, v]| (u, 1.0 - v)).collect());
+        let indices: Vec<usize> = match reader.read_indices() {
+            Some(indices) => indices.into_u32().map(|index| index as usize).collect(),
+            None => (0..positions.len()).collect(),
+        };
+        if let Some(&index) = indices.iter().find(|&&index| index >= positions.len()) {
+            return Err(format!("índice de vértice fuera de rango: {}", index));
+        }
+
+        let triangles = indices
+            .chunks_exact(3)
+            .map(|corners| {
+                let [a, b, c] = [corners[0], corners[1], corners[2]];
+                let mut triangle = Triangle::new([positions[a], positions[b], positions[c]]);
+                triangle.normals = normals
+                    .as_ref()
+                    .filter(|normals| normals.len() == positions.len())
+                    .map(|normals| [normals[a], normals[b], normals[c]]);
+                if let Some(uvs) = uvs.as_ref().filter(|uvs| uvs.len() == positions.len()) {
+                    triangle.uvs = [uvs[a], uvs[b], uvs[c]];
+                }
+                triangle
+            })
+            .collect();
+
+        let material = match primitive.material().index() {
+            Some(index) => self.materials[index].clone(),
+            None => self.default_material.clone(),
+        };
+        Ok(Some(Mesh::new(triangles, material)))
+    }
+}
+
+// metallicRoughness a los coeficientes de Phong: lo metálico y liso refleja, la
+// transmisión (KHR_materials_transmission) refracta y la rugosidad ensancha el brillo
+fn translate_material(material: &gltf::Material, images: &[gltf::image::Data]) -> Material {
+    let pbr = material.pbr_metallic_roughness();
+    let [r, g, b, a] = pbr.base_color_factor();
+    let metallic = pbr.metallic_factor().clamp(0.0, 1.0);
+    let roughness = pbr.roughness_factor().clamp(0.0, 1.0);
+    let transmission = material.transmission().map_or(0.0, |t| t.transmission_factor()).clamp(0.0, 1.0);
+
+    let specular = 10.0 + 990.0 * (1.0 - roughness).powi(2);
+    let reflect = 0.8 * metallic * (1.0 - roughness);
+    let refract = 0.8 * transmission;
+    let albedo = [0.9 * (1.0 - reflect) * (1.0 - refract), 0.2 + 0.6 * metallic, reflect, refract];
+    let refractive_index = if transmission > 0.0 { material.ior().unwrap_or(1.5) } else { 1.0 };
+
+    // El factor de color multiplica la textura; se aplica una vez al cargarla
+    let texture = pbr
+        .base_color_texture()
+        .and_then(|info| images.get(info.texture().source().index()))
+        .and_then(|image| texture_image(image, [r, g, b, a]))
+        .map(|image| Arc::new(Texture { image }));
+
+    let mut result = Material::new(linear_color([r, g, b]), specular, albedo, refractive_index, texture);
+
+    let emissive = material.emissive_factor();
+    let peak = emissive.iter().copied().fold(0.0, f32::max);
+    if peak > 0.0 {
+        let strength = peak * material.emissive_strength().unwrap_or(1.0);
+        result = result.with_emission(linear_color(emissive.map(|c| c / peak)), strength);
+    }
+    result
+}
+
+// Imagen decodificada por el crate gltf a RGBA de 8 bits; los formatos de punto
+// flotante no se usan como textura
+fn texture_image(data: &gltf::image::Data, factor: [f32; 4]) -> Option<RgbaImage> {
+    let channels = match data.format {
+        Format::R8 | Format::R16 => 1,
+        Format::R8G8 | Format::R16G16 => 2,
+        Format::R8G8B8 | Format::R16G16B16 => 3,
+        Format::R8G8B8A8 | Format::R16G16B16A16 => 4,
+        Format::R32G32B32FLOAT | Format::R32G32B32A32FLOAT => return None,
+    };
+    let pixels = (data.width * data.height) as usize;
+    let bytes_per_channel = data.pixels.len().checked_div(pixels * channels)?.max(1);
+    if data.pixels.len() < pixels * channels * bytes_per_channel {
+        return None;
+    }
+    // En little-endian el byte alto de un canal de 16 bits es el último
+    let channel = |pixel: usize, c: usize| data.pixels[(pixel * channels + c) * bytes_per_channel + bytes_per_channel - 1];
+
+    let factor = [srgb(factor[0]), srgb(factor[1]), srgb(factor[2]), factor[3]];
+    let mut image = RgbaImage::new(data.width, data.height);
+    for (pixel, out) in image.pixels_mut().enumerate() {
+        let rgba = match channels {
+            1 => [channel(pixel, 0), channel(pixel, 0), channel(pixel, 0), 255],
+            2 => [channel(pixel, 0), channel(pixel, 0), channel(pixel, 0), channel(pixel, 1)],
+            3 => [channel(pixel, 0), channel(pixel, 1), channel(pixel, 2), 255],
+            _ => [channel(pixel, 0), channel(pixel, 1), channel(pixel, 2), channel(pixel, 3)],
+        };
+        for c in 0..4 {
+            out[c] = (rgba[c] as f32 * factor[c]).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+    Some(image)
+}
+
+// glTF guarda los colores en espacio lineal; los de acá van como se ven (sRGB aproximado)
+fn srgb(value: f32) -> f32 {
+    value.clamp(0.0, 1.0).powf(1.0 / 2.2)
+}
+
+fn linear_color([r, g, b]: [f32; 3]) -> Color {
+    let channel = |value: f32| (srgb(value) * 255.0).round() as u8;
+    Color::new(channel(r), channel(g), channel(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ray::Ray;
+    use crate::scene::Scene;
+
+    // Triángulo girado 90° en Y dentro de un nodo escalado y trasladado, con una
+    // cámara de cada tipo y una luz de cada tipo; el búfer va embebido en base64
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/nodos.gltf");
+
+    fn assert_near(actual: Vec3, expected: Vec3) {
+        assert!((actual - expected).norm() < 1e-4, "{:?} != {:?}", actual, expected);
+    }
+
+    // Copia del fixture con `edit` aplicado, en un archivo temporal propio de cada prueba
+    fn load_edited(name: &str, edit: impl Fn(String) -> String) -> Result<GltfScene, String> {
+        let text = edit(std::fs::read_to_string(FIXTURE).unwrap());
+        let path = std::env::temp_dir().join(format!("proyecto2raytracing_{}_{}.gltf", std::process::id(), name));
+        std::fs::write(&path, text).unwrap();
+        let result = load(&path, &mut Assets::new());
+        let _ = std::fs::remove_file(&path);
+        result
+    }
+
+    #[test]
+    fn aplica_la_jerarquia_de_nodos() {
+        let mut assets = Assets::new();
+        let scene = load(Path::new(FIXTURE), &mut assets).unwrap();
+
+        // (0,0,0), (1,0,0), (0,1,0) girados, escalados por 2 y llevados a z = -5
+        let bounds = scene.root.bounds().unwrap();
+        assert_near(bounds.min, Vec3::new(0.0, 0.0, -7.0));
+        assert_near(bounds.max, Vec3::new(0.0, 2.0, -5.0));
+
+        let ray = Ray::new(Vec3::new(5.0, 0.5, -5.5), Vec3::new(-1.0, 0.0, 0.0));
+        let hit = scene.root.ray_intersect(&ray).unwrap();
+        assert!((hit.distance - 5.0).abs() < 1e-4);
+        assert!((hit.normal.x.abs() - 1.0).abs() < 1e-4);
+        assert!(std::ptr::eq(hit.material, &*assets.material(&format!("{}#0:rojo", FIXTURE)).unwrap()));
+
+        let miss = Ray::new(Vec3::new(5.0, 1.9, -6.9), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(scene.root.ray_intersect(&miss).is_none());
+    }
+
+    #[test]
+    fn traduce_los_materiales() {
+        let mut assets = Assets::new();
+        load(Path::new(FIXTURE), &mut assets).unwrap();
+
+        let red = assets.material(&format!("{}#0:rojo", FIXTURE)).unwrap();
+        assert_eq!(red.diffuse.to_hex(), 0xff0000);
+        assert!((red.albedo[2] - 0.8).abs() < 1e-6);
+        assert_eq!(red.albedo[3], 0.0);
+
+        let glass = assets.material(&format!("{}#1:vidrio", FIXTURE)).unwrap();
+        assert!((glass.albedo[3] - 0.8).abs() < 1e-6);
+        assert_eq!(glass.refractive_index, 1.3);
+        assert!(assets.material(&format!("{}#default", FIXTURE)).is_some());
+    }
+
+    #[test]
+    fn importa_la_camara_en_perspectiva() {
+        let scene = load(Path::new(FIXTURE), &mut Assets::new()).unwrap();
+
+        // La ortográfica se ignora
+        assert_eq!(scene.cameras.len(), 1);
+        let camera = &scene.cameras[0];
+        assert_near(camera.eye, Vec3::new(0.0, 0.0, 5.0));
+        assert_near(camera.center, Vec3::new(0.0, 0.0, 4.0));
+        assert_eq!(camera.fov, 0.8);
+    }
+
+    #[test]
+    fn convierte_las_luces() {
+        let scene = load(Path::new(FIXTURE), &mut Assets::new()).unwrap();
+        assert_eq!(scene.lights.len(), 3);
+
+        let spot = &scene.lights[0];
+        assert_near(spot.position, Vec3::new(0.0, 2.0, -5.0));
+        assert!((spot.intensity - 2.0).abs() < 1e-4);
+        let cone = spot.spot.as_ref().unwrap();
+        assert_near(cone.direction, Vec3::new(0.0, -1.0, 0.0));
+        assert_eq!((cone.inner, cone.outer), (0.2, 0.5));
+        // Fuera del cono exterior no llega nada; sobre el eje, todo
+        assert_eq!(spot.intensity_toward(&Vec3::new(0.0, 2.0, -4.0)), 0.0);
+        assert!((spot.intensity_toward(&Vec3::new(0.0, 1.0, -5.0)) - spot.intensity_at(1.0)).abs() < 1e-6);
+
+        let point = &scene.lights[1];
+        assert_near(point.position, Vec3::new(2.0, 0.0, -5.0));
+        assert!((point.intensity - 5.0).abs() < 1e-4);
+        assert_eq!(point.attenuation, 1.0);
+        assert!(point.spot.is_none());
+
+        let directional = &scene.lights[2];
+        assert_near(directional.position, Vec3::new(0.0, 0.0, DIRECTIONAL_DISTANCE));
+        assert!((directional.intensity - 1.0).abs() < 1e-4);
+        assert_eq!(directional.attenuation, 0.0);
+    }
+
+    #[test]
+    fn saltea_los_nodos_con_escala_nula() {
+        let scene = load_edited("escala_nula", |text| {
+            text.replace("\"name\": \"punto\",", "\"name\": \"punto\", \"scale\": [0, 1, 1],")
+        })
+        .unwrap();
+        // La luz del nodo escondido no se importa; las demás sí
+        assert_eq!(scene.lights.len(), 2);
+        assert!(scene.lights.iter().all(|light| (light.position - Vec3::new(2.0, 0.0, -5.0)).norm() > 1.0));
+
+        // Escondido el triángulo, no queda geometría
+        let scene = load_edited("sin_geometria", |text| {
+            text.replace("\"name\": \"triangulo\",", "\"name\": \"triangulo\", \"scale\": [1, 1, 0],")
+        })
+        .unwrap();
+        assert!(scene.root.bounds().is_none());
+        assert_eq!(scene.cameras.len(), 1);
+    }
+
+    #[test]
+    fn solo_el_sol_sigue_el_ciclo_de_dia() {
+        // Abierto directo, la escena agrega el sol como primera luz
+        let mut scene = Scene::from_file(Path::new(FIXTURE)).unwrap();
+        assert_eq!(scene.sun, Some(0));
+        let before: Vec<Vec3> = scene.lights.iter().map(|light| light.position).collect();
+        scene.set_time(20.0);
+        assert!((scene.lights[0].position - before[0]).norm() > 1.0);
+        for (light, position) in scene.lights[1..].iter().zip(&before[1..]) {
+            assert_eq!(light.position, *position);
+        }
+
+        // Importado desde un TOML sin sol, ninguna luz se mueve
+        let path = std::env::temp_dir().join(format!("proyecto2raytracing_{}_sin_sol.toml", std::process::id()));
+        std::fs::write(&path, format!("[[objects]]\ntype = \"gltf\"\npath = '{}'\n", FIXTURE)).unwrap();
+        let result = Scene::from_file(&path);
+        let _ = std::fs::remove_file(&path);
+        let mut scene = result.unwrap();
+        assert_eq!(scene.sun, None);
+        assert_eq!(scene.lights.len(), 3);
+        let before: Vec<(Vec3, f32)> = scene.lights.iter().map(|light| (light.position, light.intensity)).collect();
+        scene.set_time(20.0);
+        let after: Vec<(Vec3, f32)> = scene.lights.iter().map(|light| (light.position, light.intensity)).collect();
+        assert_eq!(after, before);
+    }
+}
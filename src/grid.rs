@@ -1,5 +1,7 @@
 use nalgebra_glm::Vec3;
-use crate::ray_intersect::{RayIntersect, Intersect};
+use std::sync::Arc;
+use crate::ray::Ray;
+use crate::ray_intersect::{Aabb, RayIntersect, Hit, Occlusion, bounds_of, closest_hit, any_occlusion};
 use crate::cube::Cube;  
 use crate::material::Material;
 pub struct Grid {
@@ -11,7 +13,7 @@ impl Grid {
         Grid { objects }
     }
 
-    pub fn create_cuadricula(width: usize, depth: usize, cube_size: f32, material: Material, offset_x: f32, offset_y: f32, offset_z: f32) -> Self {
+    pub fn create_cuadricula(width: usize, depth: usize, cube_size: f32, material: Arc<Material>, offset_x: f32, offset_y: f32, offset_z: f32) -> Self {
         let mut objects: Vec<Box<dyn RayIntersect>> = Vec::new();
     
         for x in 0..width {
@@ -36,18 +38,21 @@ impl Grid {
 }
 
 impl RayIntersect for Grid {
-    fn ray_intersect(&self, origin: &Vec3, direction: &Vec3) -> Intersect {
-        let mut closest_intersect = Intersect::empty();
-        let mut min_distance = f32::INFINITY;
-
-        for object in &self.objects {
-            let intersect = object.ray_intersect(origin, direction);
-            if intersect.is_intersecting && intersect.distance < min_distance {
-                min_distance = intersect.distance;
-                closest_intersect = intersect;
-            }
+    fn ray_intersect(&self, ray: &Ray) -> Option<Hit<'_>> {
+        closest_hit(&self.objects, ray)
+    }
+
+    fn occlusion(&self, ray: &Ray) -> Occlusion {
+        any_occlusion(&self.objects, ray)
+    }
+
+    fn update(&mut self, time: f32) {
+        for object in &mut self.objects {
+            object.update(time);
         }
+    }
 
-        closest_intersect
+    fn bounds(&self) -> Option<Aabb> {
+        bounds_of(&self.objects)
     }
 }
@@ -1,38 +1,106 @@
-use nalgebra_glm::Vec3;
-use crate::ray_intersect::{RayIntersect, Intersect};
+use nalgebra_glm::{Mat3, Quat, Vec3};
+use crate::ray::Ray;
+use crate::animation::Track;
+use crate::ray_intersect::{Aabb, RayIntersect, Hit, Occlusion, bounds_of, closest_hit, any_occlusion};
 pub struct Group {
     pub elements: Vec<Box<dyn RayIntersect>>,
     pub offset: Vec3, 
+    pub offset_track: Option<Track<Vec3>>,
+    pub rotation_track: Option<Track<Quat>>,
+    pub scale_track: Option<Track<Vec3>>,
+    transform: Option<(Mat3, Mat3)>, // (lineal, inversa); se aplica antes del offset
 }
 
 impl Group {
     pub fn new(elements: Vec<Box<dyn RayIntersect>>, offset: Vec3) -> Self {
-        Group { elements, offset }
+        Group { elements, offset, offset_track: None, rotation_track: None, scale_track: None, transform: None }
     }
 
-
+    // Rotación, escala o cualquier parte lineal de una transformación, como las de
+    // los nodos de glTF. Tiene que ser invertible para poder llevar los rayos al
+    // espacio del grupo.
+    pub fn with_transform(mut self, linear: Mat3) -> Result<Self, String> {
+        let inverse = invert(&linear).ok_or_else(|| "la transformación del grupo no es invertible".to_string())?;
+        self.transform = Some((linear, inverse));
+        Ok(self)
+    }
 
     pub fn set_offset(&mut self, offset: Vec3) {
         self.offset = offset;
     }
+
+    // El offset del grupo sigue el track en cada `update`
+    pub fn with_offset_track(mut self, track: Track<Vec3>) -> Self {
+        self.offset_track = Some(track);
+        self
+    }
+
+    // Con alguno de estos tracks la parte lineal del grupo pasa a ser la rotación
+    // por la escala de cada `update`; el que falte queda en identidad
+    pub fn with_rotation_track(mut self, track: Track<Quat>) -> Self {
+        self.rotation_track = Some(track);
+        self
+    }
+
+    pub fn with_scale_track(mut self, track: Track<Vec3>) -> Self {
+        self.scale_track = Some(track);
+        self
+    }
+}
+
+// Inversa de una transformación lineal, si existe y es finita
+pub(crate) fn invert(linear: &Mat3) -> Option<Mat3> {
+    linear.try_inverse().filter(|inverse| inverse.iter().all(|c| c.is_finite()))
 }
 
 impl RayIntersect for Group {
-    fn ray_intersect(&self, origin: &Vec3, direction: &Vec3) -> Intersect {
-        let mut closest_intersect = Intersect::empty();
-        let mut min_distance = f32::INFINITY;
-
-        for element in &self.elements {
-            let adjusted_origin = origin - self.offset;
-            let adjusted_direction = direction; 
-
-            let intersect = element.ray_intersect(&adjusted_origin, &adjusted_direction);
-            if intersect.is_intersecting && intersect.distance < min_distance {
-                min_distance = intersect.distance;
-                closest_intersect = intersect;
+    fn ray_intersect(&self, ray: &Ray) -> Option<Hit<'_>> {
+        // Los elementos viven en el espacio local del grupo; el impacto se devuelve en coordenadas del mundo
+        let Some((_, inverse)) = &self.transform else {
+            return closest_hit(&self.elements, &ray.translated(&self.offset)).map(|mut hit| {
+                hit.point += self.offset;
+                hit
+            });
+        };
+
+        closest_hit(&self.elements, &ray.transformed(inverse, &self.offset)).map(|mut hit| {
+            hit.point = ray.at(hit.distance);
+            // Las normales se transforman con la inversa transpuesta
+            hit.normal = (inverse.transpose() * hit.normal).normalize();
+            hit
+        })
+    }
+
+    fn occlusion(&self, ray: &Ray) -> Occlusion {
+        match &self.transform {
+            Some((_, inverse)) => any_occlusion(&self.elements, &ray.transformed(inverse, &self.offset)),
+            None => any_occlusion(&self.elements, &ray.translated(&self.offset)),
+        }
+    }
+
+    fn update(&mut self, time: f32) {
+        if let Some(offset) = self.offset_track.as_ref().and_then(|t| t.sample(time)) {
+            self.offset = offset;
+        }
+        if self.rotation_track.is_some() || self.scale_track.is_some() {
+            let rotation = self.rotation_track.as_ref().and_then(|t| t.sample(time)).unwrap_or_else(Quat::identity);
+            let scale = self.scale_track.as_ref().and_then(|t| t.sample(time)).unwrap_or_else(|| Vec3::repeat(1.0));
+            let linear = nalgebra_glm::quat_to_mat3(&rotation) * Mat3::from_diagonal(&scale);
+            // Una escala nula en el track deja la transformación anterior
+            if let Some(inverse) = invert(&linear) {
+                self.transform = Some((linear, inverse));
             }
         }
+        for element in &mut self.elements {
+            element.update(time);
+        }
+    }
 
-        closest_intersect
+    fn bounds(&self) -> Option<Aabb> {
+        let bounds = bounds_of(&self.elements)?;
+        Some(match &self.transform {
+            Some((linear, _)) => bounds.transformed(linear, &self.offset),
+            None => bounds.translated(&self.offset),
+        })
     }
 }
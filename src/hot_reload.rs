@@ -0,0 +1,48 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::scene::Scene;
+use crate::scene_file;
+
+// Revisar las fechas de los archivos es barato, pero no hace falta en cada cuadro
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Vigila el archivo de escena y todo lo que lee (texturas, recorrido de cámara)
+// comparando sus fechas de modificación
+pub struct SceneWatcher {
+    path: PathBuf,
+    files: Vec<(PathBuf, Option<SystemTime>)>, // None si el archivo no existía
+    last_poll: Instant,
+}
+
+impl SceneWatcher {
+    pub fn new(path: &Path) -> Self {
+        SceneWatcher { path: path.to_path_buf(), files: Vec::new(), last_poll: Instant::now() }
+    }
+
+    // Carga la escena y pasa a vigilar lo que se leyó, aunque la carga falle:
+    // así se reintenta cuando se corrija el archivo que tenía el error
+    pub fn load(&mut self) -> Result<Scene, String> {
+        let mut sources = Vec::new();
+        let scene = scene_file::load_tracked(&self.path, &mut sources);
+        self.files = sources.into_iter().map(|path| {
+            let modified = modified(&path);
+            (path, modified)
+        }).collect();
+        self.last_poll = Instant::now();
+        scene
+    }
+
+    // true si algún archivo cambió, apareció o desapareció desde la última carga
+    pub fn changed(&mut self) -> bool {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = Instant::now();
+        self.files.iter().any(|(path, recorded)| modified(path) != *recorded)
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
@@ -0,0 +1,113 @@
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::font;
+use crate::framebuffer::{Framebuffer, Paint};
+use crate::render_thread::FrameStats;
+
+const MARGIN: usize = 8;
+const PADDING: usize = 6;
+const PANEL_COLOR: Color = Color::new(16, 24, 32);
+const PANEL_ALPHA: f32 = 0.75;
+const TEXT_COLOR: Color = Color::new(232, 232, 232);
+const ACCENT_COLOR: Color = Color::new(255, 200, 80);
+const ERROR_PANEL_COLOR: Color = Color::new(64, 12, 12);
+const ERROR_COLOR: Color = Color::new(255, 96, 80);
+
+// Peso del valor nuevo en el promedio, para que las cifras no bailen
+const SMOOTHING: f32 = 0.1;
+
+// Panel de estadísticas dibujado sobre el cuadro ya renderizado. Los FPS y los
+// rayos por segundo son los del hilo de render, no los de la ventana.
+#[derive(Default)]
+pub struct Hud {
+    pub visible: bool,
+    frame_time: f32,      // segundos de render por cuadro entero, suavizado
+    rays_per_second: f32, // suavizado
+}
+
+impl Hud {
+    pub fn new() -> Self {
+        Hud::default()
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    // Se llama en cada vuelta de la ventana con lo último que publicó el render
+    pub fn record_frame(&mut self, stats: &FrameStats) {
+        let Some(frame) = stats.last_frame.filter(|frame| !frame.render_time.is_zero()) else {
+            return;
+        };
+        let frame_time = frame.render_time.as_secs_f32();
+        let rays_per_second = frame.rays.total() as f32 / frame_time;
+        if self.frame_time == 0.0 {
+            self.frame_time = frame_time;
+            self.rays_per_second = rays_per_second;
+        } else {
+            self.frame_time += (frame_time - self.frame_time) * SMOOTHING;
+            self.rays_per_second += (rays_per_second - self.rays_per_second) * SMOOTHING;
+        }
+    }
+
+    // `stats` es del último cuadro publicado por el hilo de render
+    pub fn draw(&self, framebuffer: &mut Framebuffer, stats: &FrameStats, camera: &Camera) {
+        if !self.visible {
+            return;
+        }
+
+        let fps = if self.frame_time > 0.0 { 1.0 / self.frame_time } else { 0.0 };
+        let rays = stats.last_frame.unwrap_or_default().rays;
+        let lines = [
+            format!("{:.1} FPS  {:.1} ms render{}", fps, self.frame_time * 1000.0,
+                if stats.finished { "" } else { " (parcial)" }),
+            format!("{:.2} Mrayos/s  {}k por cuadro", self.rays_per_second / 1e6, rays.total() / 1000),
+            format!("prim {}k sec {}k sombra {}k", rays.primary / 1000, rays.secondary / 1000, rays.shadow / 1000),
+            format!("t {:.2}s  dia {:.3}", stats.time, stats.day_fraction),
+            match stats.sun {
+                Some(sun) => format!("sol    ({:.2}, {:.2}, {:.2})", sun.x, sun.y, sun.z),
+                None => "sol    -".to_string(),
+            },
+            format!("ojo    ({:.2}, {:.2}, {:.2})", camera.eye.x, camera.eye.y, camera.eye.z),
+            format!("centro ({:.2}, {:.2}, {:.2})", camera.center.x, camera.center.y, camera.center.z),
+        ];
+
+        let width = lines.iter().map(|line| font::text_width(line, 1)).max().unwrap_or(0) + 2 * PADDING;
+        let height = lines.len() * font::LINE_HEIGHT + 2 * PADDING;
+
+        let (left, top) = (MARGIN as isize, MARGIN as isize);
+        framebuffer.fill_rect(left, top, width, height, Paint::new(PANEL_COLOR, PANEL_ALPHA));
+
+        // Barra de acento a la izquierda y separador bajo los tiempos
+        let separator_y = top + (PADDING + font::LINE_HEIGHT) as isize - 2;
+        framebuffer.line(left, top, left, top + height as isize - 1, ACCENT_COLOR);
+        framebuffer.line(left + PADDING as isize, separator_y, left + (width - PADDING) as isize, separator_y, ACCENT_COLOR);
+
+        let padding = PADDING as isize;
+        framebuffer.text(left + padding, top + padding, &lines.join("\n"), 1, TEXT_COLOR);
+    }
+}
+
+// Panel con el error de carga de la escena, abajo y a todo el ancho de la ventana.
+// Se dibuja aunque el HUD esté oculto.
+pub fn draw_error(framebuffer: &mut Framebuffer, message: &str) {
+    let width = framebuffer.width.saturating_sub(2 * MARGIN);
+    let columns = (width.saturating_sub(2 * PADDING) / font::ADVANCE).max(1);
+
+    let mut lines = vec!["Error al cargar la escena".to_string()];
+    for line in message.lines() {
+        let chars: Vec<char> = line.chars().collect();
+        lines.extend(chars.chunks(columns).map(|chunk| chunk.iter().collect::<String>()));
+    }
+    let height = lines.len() * font::LINE_HEIGHT + 2 * PADDING;
+
+    let left = MARGIN as isize;
+    let top = framebuffer.height as isize - (MARGIN + height) as isize;
+    framebuffer.fill_rect(left, top, width, height, Paint::new(ERROR_PANEL_COLOR, PANEL_ALPHA));
+    framebuffer.line(left, top, left, top + height as isize - 1, ERROR_COLOR);
+
+    let padding = PADDING as isize;
+    framebuffer.text(left + padding, top + padding, &lines[0], 1, ERROR_COLOR);
+    let body_top = top + padding + font::LINE_HEIGHT as isize;
+    framebuffer.text(left + padding, body_top, &lines[1..].join("\n"), 1, TEXT_COLOR);
+}
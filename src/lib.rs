@@ -0,0 +1,78 @@
+// Trazador de rayos como biblioteca: construcción de escenas, render de cuadros
+// y exportación. La ventana con minifb es un binario aparte (feature `window`).
+//
+// Los módulos públicos son la API estable; el resto es interno y puede cambiar.
+
+pub mod framebuffer;
+pub mod ray;
+pub mod ray_intersect;
+pub mod color;
+pub mod camera;
+pub mod light;
+pub mod material;
+pub mod cube;
+pub mod grid;
+pub mod group;
+pub mod texture;
+pub mod assets;
+pub mod water;
+pub(crate) mod sampling;
+pub mod ambient;
+pub mod media;
+pub mod animation;
+pub mod day_cycle;
+pub mod camera_path;
+pub mod scene;
+pub mod mesh;
+pub mod voxel;
+pub(crate) mod vox;
+pub(crate) mod gltf_import;
+pub(crate) mod scene_file;
+pub mod aov;
+pub mod denoise;
+pub(crate) mod font;
+pub mod renderer;
+pub mod export;
+
+// Solo para la ventana
+#[cfg(feature = "window")]
+mod config;
+#[cfg(feature = "window")]
+mod debug_view;
+#[cfg(feature = "window")]
+mod gizmos;
+#[cfg(feature = "window")]
+mod hot_reload;
+#[cfg(feature = "window")]
+mod hud;
+#[cfg(feature = "window")]
+mod progressive;
+#[cfg(feature = "window")]
+mod render_thread;
+
+pub use crate::assets::Assets;
+pub use crate::camera::Camera;
+pub use crate::color::Color;
+pub use crate::cube::Cube;
+pub use crate::export::{ExportOptions, export_animation};
+pub use crate::framebuffer::Framebuffer;
+pub use crate::grid::Grid;
+pub use crate::group::Group;
+pub use crate::light::Light;
+pub use crate::material::Material;
+pub use crate::ray_intersect::RayIntersect;
+pub use crate::renderer::{RenderSettings, Renderer};
+pub use crate::scene::Scene;
+pub use crate::texture::Texture;
+
+// Lo que usa el binario de la ventana: línea de comandos, hilo de render, HUD y
+// recarga en caliente. No forma parte de la API estable.
+#[cfg(feature = "window")]
+#[doc(hidden)]
+pub mod window {
+    pub use crate::config::Config;
+    pub use crate::debug_view::DebugView;
+    pub use crate::hot_reload::SceneWatcher;
+    pub use crate::hud::{Hud, draw_error};
+    pub use crate::render_thread::{RenderRequest, RenderThread, ViewSettings};
+}
@@ -6,6 +6,16 @@ pub struct Light {
     pub position: Vec3,
     pub color: Color,
     pub intensity: f32,
+    pub attenuation: f32, // 0.0 = sin atenuación (el sol)
+    pub spot: Option<Spot>,
+}
+
+// Cono de un foco: alumbra entero hasta `inner` radianes del eje y se apaga en `outer`
+#[derive(Debug, Clone, Copy)]
+pub struct Spot {
+    pub direction: Vec3,
+    pub inner: f32,
+    pub outer: f32,
 }
 
 impl Light {
@@ -14,6 +24,44 @@ impl Light {
             position,
             color,
             intensity,
+            attenuation: 0.0,
+            spot: None,
+        }
+    }
+
+    pub fn with_attenuation(mut self, attenuation: f32) -> Self {
+        self.attenuation = attenuation;
+        self
+    }
+
+    pub fn with_spot(mut self, direction: Vec3, inner: f32, outer: f32) -> Self {
+        self.spot = Some(Spot { direction: direction.normalize(), inner, outer });
+        self
+    }
+
+    // Intensidad que llega a un punto a la distancia dada
+    pub fn intensity_at(&self, distance: f32) -> f32 {
+        self.intensity / (1.0 + self.attenuation * distance * distance)
+    }
+
+    // Intensidad que llega a `point`, con la atenuación y el cono del foco
+    pub fn intensity_toward(&self, point: &Vec3) -> f32 {
+        let to_point = point - self.position;
+        let distance = to_point.magnitude();
+        let intensity = self.intensity_at(distance);
+        match &self.spot {
+            Some(spot) if distance > 0.0 => intensity * spot.falloff(&(to_point / distance)),
+            _ => intensity,
         }
     }
 }
+
+impl Spot {
+    // Caída suave entre los dos ángulos, la que sugiere KHR_lights_punctual
+    fn falloff(&self, direction: &Vec3) -> f32 {
+        let (cos_inner, cos_outer) = (self.inner.cos(), self.outer.cos());
+        let scale = 1.0 / (cos_inner - cos_outer).max(1e-3);
+        let factor = ((self.direction.dot(direction) - cos_outer) * scale).clamp(0.0, 1.0);
+        factor * factor
+    }
+}
@@ -1,5 +1,7 @@
+use std::sync::Arc;
 use crate::color::Color;
 use crate::texture::Texture;
+use crate::media::Medium;
 
 #[derive(Debug, Clone)]
 pub struct Material {
@@ -7,8 +9,11 @@ pub struct Material {
     pub specular: f32,
     pub albedo: [f32; 4],
     pub refractive_index: f32,
-    pub texture: Option<Texture>,  
-    
+    pub texture: Option<Arc<Texture>>,  
+    pub emission: Color,
+    pub emission_strength: f32,
+    pub medium: Option<Medium>, // Volumen que llena el interior del objeto
+    pub id: u32, // Lo asigna Assets; 0 si el material no está registrado
 }
 
 impl Material {
@@ -17,7 +22,7 @@ impl Material {
         specular: f32,
         albedo: [f32; 4],
         refractive_index: f32,
-        texture: Option<Texture>,  
+        texture: Option<Arc<Texture>>,  
     ) -> Self {
         Material {
             diffuse,
@@ -25,9 +30,33 @@ impl Material {
             albedo,
             refractive_index,
             texture,
+            emission: Color::black(),
+            emission_strength: 0.0,
+            medium: None,
+            id: 0,
         }
     }
 
+    // Hace que el material brille con su propio color, independiente de las luces
+    pub fn with_emission(mut self, emission: Color, emission_strength: f32) -> Self {
+        self.emission = emission;
+        self.emission_strength = emission_strength;
+        self
+    }
+
+    pub fn with_medium(mut self, medium: Medium) -> Self {
+        self.medium = Some(medium);
+        self
+    }
+
+    pub fn is_emissive(&self) -> bool {
+        self.emission_strength > 0.0 && self.emission.to_hex() != 0
+    }
+
+    pub fn emitted(&self) -> Color {
+        self.emission * self.emission_strength
+    }
+
     pub fn shade(&self, uv: (f32, f32)) -> Color {
         if let Some(texture) = &self.texture {
             let tex_color = texture.get_color_at(uv);
@@ -44,6 +73,10 @@ impl Material {
             albedo: [0.0, 0.0, 0.0, 0.0],
             refractive_index: 0.0,
             texture: None,  
+            emission: Color::black(),
+            emission_strength: 0.0,
+            medium: None,
+            id: 0,
         }
     }
 }
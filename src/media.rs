@@ -0,0 +1,161 @@
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+use crate::color::Color;
+use crate::light::Light;
+use crate::ray::{Ray, RayKind, count_ray};
+use crate::ray_intersect::{Occlusion, RayIntersect, any_occlusion};
+use crate::sampling::Sampler;
+
+// Niebla global: exponencial con la distancia, más densa cerca del suelo, y
+// opcionalmente con dispersión simple de la luz principal (rayos de luz)
+#[derive(Debug, Clone)]
+pub struct Fog {
+    pub color: Color,
+    pub density: f32,         // Niebla por distancia, 0 = apagada
+    pub height_density: f32,  // Densidad extra a la altura `base_height`
+    pub height_falloff: f32,  // Qué tan rápido se disipa al subir
+    pub base_height: f32,
+    pub sky_distance: f32,    // Distancia que se asume para los rayos que no chocan con nada
+    pub scattering: f32,      // Fuerza de los rayos de luz, 0 = apagados
+    pub shaft_steps: u32,
+    pub anisotropy: f32,      // g de Henyey-Greenstein: > 0 dispersa hacia adelante
+}
+
+impl Fog {
+    pub fn new(color: Color, density: f32) -> Self {
+        Fog {
+            color,
+            density,
+            height_density: 0.0,
+            height_falloff: 1.0,
+            base_height: 0.0,
+            sky_distance: 12.0,
+            scattering: 0.0,
+            shaft_steps: 0,
+            anisotropy: 0.0,
+        }
+    }
+
+    pub fn none() -> Self {
+        Fog::new(Color::black(), 0.0)
+    }
+
+    pub fn with_height_fog(mut self, height_density: f32, height_falloff: f32, base_height: f32) -> Self {
+        self.height_density = height_density;
+        self.height_falloff = height_falloff;
+        self.base_height = base_height;
+        self
+    }
+
+    pub fn with_sky_distance(mut self, sky_distance: f32) -> Self {
+        self.sky_distance = sky_distance;
+        self
+    }
+
+    pub fn with_light_shafts(mut self, scattering: f32, steps: u32, anisotropy: f32) -> Self {
+        self.scattering = scattering;
+        self.shaft_steps = steps;
+        self.anisotropy = anisotropy;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.density > 0.0 || self.height_density > 0.0
+    }
+
+    fn density_at(&self, point: &Vec3) -> f32 {
+        self.density + self.height_density * (-self.height_falloff * (point.y - self.base_height)).exp()
+    }
+
+    // Espesor óptico a lo largo del rayo entre 0 y `distance`, integrado analíticamente
+    fn optical_depth(&self, ray: &Ray, distance: f32) -> f32 {
+        let mut depth = self.density * distance;
+
+        if self.height_density > 0.0 {
+            let start = self.height_density * (-self.height_falloff * (ray.origin.y - self.base_height)).exp();
+            let rise = self.height_falloff * ray.direction.y;
+            depth += if rise.abs() < 1e-5 {
+                start * distance
+            } else {
+                start * (1.0 - (-rise * distance).exp()) / rise
+            };
+        }
+
+        depth
+    }
+
+    pub fn transmittance(&self, ray: &Ray, distance: f32) -> f32 {
+        (-self.optical_depth(ray, distance)).exp()
+    }
+
+    // Mezcla el color visto a `distance` con el de la niebla; None = el rayo no chocó
+    pub fn apply(&self, color: Color, ray: &Ray, distance: Option<f32>) -> Color {
+        if !self.is_enabled() {
+            return color;
+        }
+        let distance = distance.unwrap_or(self.sky_distance);
+        let transmittance = self.transmittance(ray, distance);
+        color * transmittance + self.color * (1.0 - transmittance)
+    }
+
+    // Luz de `light` dispersada hacia la cámara a lo largo del rayo. Se marcha con
+    // un desplazamiento aleatorio por píxel para cambiar bandas por ruido.
+    pub fn light_shafts(&self, ray: &Ray, distance: Option<f32>, light: &Light, objects: &[Box<dyn RayIntersect>], sampler: &mut Sampler) -> Color {
+        if self.scattering <= 0.0 || self.shaft_steps == 0 || !self.is_enabled() {
+            return Color::black();
+        }
+
+        let distance = distance.unwrap_or(self.sky_distance);
+        let step = distance / self.shaft_steps as f32;
+        let jitter = sampler.next_f32();
+        let mut in_scattered = 0.0;
+
+        for i in 0..self.shaft_steps {
+            let t = (i as f32 + jitter) * step;
+            let point = ray.at(t);
+
+            let to_light = light.position - point;
+            let light_distance = to_light.magnitude();
+            let light_dir = to_light / light_distance;
+
+            let shadow_ray = Ray::new(point, light_dir).with_range(0.0, light_distance);
+            count_ray(RayKind::Shadow);
+            if any_occlusion(objects, &shadow_ray) == Occlusion::Opaque {
+                continue;
+            }
+
+            let phase = henyey_greenstein(ray.direction.dot(&light_dir), self.anisotropy);
+            in_scattered += self.density_at(&point)
+                * self.transmittance(ray, t)
+                * phase
+                * light.intensity_toward(&point)
+                * step;
+        }
+
+        light.color * (self.scattering * in_scattered)
+    }
+}
+
+fn henyey_greenstein(cos_theta: f32, g: f32) -> f32 {
+    let denominator = 1.0 + g * g - 2.0 * g * cos_theta;
+    (1.0 - g * g) / (4.0 * PI * denominator * denominator.sqrt())
+}
+
+// Medio homogéneo dentro de un objeto (p. ej. la turbidez del agua). Se aplica a
+// los tramos de rayo que viajan por el interior del objeto.
+#[derive(Debug, Clone, Copy)]
+pub struct Medium {
+    pub color: Color,
+    pub density: f32,
+}
+
+impl Medium {
+    pub fn new(color: Color, density: f32) -> Self {
+        Medium { color, density }
+    }
+
+    pub fn apply(&self, color: Color, distance: f32) -> Color {
+        let transmittance = (-self.density * distance).exp();
+        color * transmittance + self.color * (1.0 - transmittance)
+    }
+}
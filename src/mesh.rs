@@ -0,0 +1,222 @@
+use std::sync::Arc;
+use nalgebra_glm::Vec3;
+use crate::cube::next_object_id;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::ray_intersect::{Aabb, RayIntersect, Hit, Occlusion, count_intersection_test};
+
+// Triángulos por hoja del BVH
+const LEAF_SIZE: usize = 4;
+// Por debajo de esto el rayo se considera paralelo al triángulo
+const DETERMINANT_EPSILON: f32 = 1e-9;
+
+#[derive(Debug, Clone)]
+pub struct Triangle {
+    pub vertices: [Vec3; 3],
+    pub normals: Option<[Vec3; 3]>, // sin normales por vértice se usa la de la cara
+    pub uvs: [(f32, f32); 3],
+}
+
+impl Triangle {
+    pub fn new(vertices: [Vec3; 3]) -> Self {
+        Triangle { vertices, normals: None, uvs: [(0.0, 0.0); 3] }
+    }
+
+    fn bounds(&self) -> Aabb {
+        let [a, b, c] = self.vertices;
+        Aabb::new(a.inf(&b).inf(&c), a.sup(&b).sup(&c))
+    }
+
+    fn centroid(&self) -> Vec3 {
+        (self.vertices[0] + self.vertices[1] + self.vertices[2]) / 3.0
+    }
+
+    // Möller-Trumbore: distancia y coordenadas baricéntricas (u, v) del impacto
+    fn intersect(&self, ray: &Ray) -> Option<(f32, f32, f32)> {
+        count_intersection_test();
+        let [a, b, c] = self.vertices;
+        let (edge1, edge2) = (b - a, c - a);
+        let p = ray.direction.cross(&edge2);
+        let determinant = edge1.dot(&p);
+        if determinant.abs() < DETERMINANT_EPSILON {
+            return None;
+        }
+        let inverse = 1.0 / determinant;
+
+        let s = ray.origin - a;
+        let u = s.dot(&p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(&edge1);
+        let v = ray.direction.dot(&q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = edge2.dot(&q) * inverse;
+        (t >= ray.t_min && t <= ray.t_max).then_some((t, u, v))
+    }
+}
+
+// Nodo del BVH: una caja con dos hijos o con un rango de triángulos
+#[derive(Debug, Clone)]
+struct BvhNode {
+    bounds: Aabb,
+    first: usize, // primer triángulo si es hoja, si no el hijo izquierdo
+    count: usize, // 0 si no es hoja; el hijo derecho es el nodo siguiente al subárbol izquierdo
+    right: usize,
+}
+
+// Malla de triángulos con un solo material, recorrida con un BVH. Pensada para
+// geometría importada; las caras se ven de los dos lados salvo en materiales
+// transparentes, que necesitan saber si el rayo entra o sale.
+pub struct Mesh {
+    triangles: Vec<Triangle>,
+    nodes: Vec<BvhNode>,
+    pub material: Arc<Material>,
+    pub id: u32,
+}
+
+impl Mesh {
+    pub fn new(mut triangles: Vec<Triangle>, material: Arc<Material>) -> Self {
+        let mut nodes = Vec::new();
+        if !triangles.is_empty() {
+            let count = triangles.len();
+            build_node(&mut triangles, 0, count, &mut nodes);
+        }
+        Mesh { triangles, nodes, material, id: next_object_id() }
+    }
+
+    // Triángulo más cercano: (índice, distancia, u, v)
+    fn closest(&self, ray: &Ray) -> Option<(usize, f32, f32, f32)> {
+        let mut closest = None;
+        self.traverse(ray, |index, triangle, t_max| {
+            let (t, u, v) = triangle.intersect(&Ray { t_max, ..*ray })?;
+            closest = Some((index, t, u, v));
+            Some(t)
+        });
+        closest
+    }
+
+    // Recorre las hojas cuya caja toca el rayo. `visit` recibe el t máximo vigente
+    // y devuelve un t menor si encontró algo, para podar el resto del árbol.
+    fn traverse(&self, ray: &Ray, mut visit: impl FnMut(usize, &Triangle, f32) -> Option<f32>) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let mut t_max = ray.t_max;
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !hits_box(&node.bounds, ray, t_max) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(node.right);
+                stack.push(node.first);
+                continue;
+            }
+            for triangle in node.first..node.first + node.count {
+                if let Some(t) = visit(triangle, &self.triangles[triangle], t_max) {
+                    t_max = t;
+                }
+            }
+        }
+    }
+}
+
+// Ordena los triángulos [start, end) por el eje más largo y los parte a la mitad
+fn build_node(triangles: &mut [Triangle], start: usize, end: usize, nodes: &mut Vec<BvhNode>) -> usize {
+    let bounds = triangles[start..end]
+        .iter()
+        .map(Triangle::bounds)
+        .reduce(|a, b| a.union(&b))
+        .unwrap_or(Aabb::new(Vec3::zeros(), Vec3::zeros()));
+    let index = nodes.len();
+    nodes.push(BvhNode { bounds, first: start, count: end - start, right: 0 });
+    if end - start <= LEAF_SIZE {
+        return index;
+    }
+
+    let extent = bounds.max - bounds.min;
+    let axis = if extent.x >= extent.y && extent.x >= extent.z { 0 } else if extent.y >= extent.z { 1 } else { 2 };
+    triangles[start..end].sort_by(|a, b| a.centroid()[axis].total_cmp(&b.centroid()[axis]));
+
+    let middle = (start + end) / 2;
+    let left = build_node(triangles, start, middle, nodes);
+    let right = build_node(triangles, middle, end, nodes);
+    nodes[index] = BvhNode { bounds, first: left, count: 0, right };
+    index
+}
+
+// Prueba de losas contra la caja de un nodo, dentro de [t_min, t_max]
+fn hits_box(bounds: &Aabb, ray: &Ray, t_max: f32) -> bool {
+    bounds.slab(ray).is_some_and(|(t_near, _, t_far, _)| t_near <= t_max && t_far >= ray.t_min)
+}
+
+impl RayIntersect for Mesh {
+    fn ray_intersect(&self, ray: &Ray) -> Option<Hit<'_>> {
+        let (index, distance, u, v) = self.closest(ray)?;
+        let triangle = &self.triangles[index];
+        let [a, b, c] = triangle.vertices;
+        let w = 1.0 - u - v;
+
+        // La cara de adelante es la de orden antihorario, como en glTF
+        let face_normal = (b - a).cross(&(c - a)).normalize();
+        let mut normal = match triangle.normals {
+            Some([na, nb, nc]) => {
+                let normal = (na * w + nb * u + nc * v).normalize();
+                if !normal.iter().all(|c| c.is_finite()) {
+                    face_normal
+                } else if normal.dot(&face_normal) < 0.0 {
+                    -normal
+                } else {
+                    normal
+                }
+            }
+            None => face_normal,
+        };
+        let mut front_face = ray.direction.dot(&face_normal) < 0.0;
+        // Una superficie opaca vista de atrás se sombrea como si fuera su frente
+        if !front_face && self.material.albedo[3] == 0.0 {
+            front_face = true;
+            normal = -normal;
+        }
+
+        let [uva, uvb, uvc] = triangle.uvs;
+        // Fuera de [0, 1] las UV se repiten, como en el modo REPEAT de glTF
+        let wrap = |value: f32| if (0.0..=1.0).contains(&value) { value } else { value.rem_euclid(1.0) };
+        let uv = (wrap(uva.0 * w + uvb.0 * u + uvc.0 * v), wrap(uva.1 * w + uvb.1 * u + uvc.1 * v));
+
+        Some(Hit {
+            point: ray.at(distance),
+            normal,
+            distance,
+            front_face,
+            uv,
+            object_id: self.id,
+            material: &self.material,
+        })
+    }
+
+    fn occlusion(&self, ray: &Ray) -> Occlusion {
+        let mut hit = false;
+        self.traverse(ray, |_, triangle, _| {
+            if hit {
+                return None;
+            }
+            hit = triangle.intersect(ray).is_some();
+            // Con un impacto alcanza; t = -inf poda todo lo que queda
+            hit.then_some(f32::NEG_INFINITY)
+        });
+        match hit {
+            true if self.material.albedo[3] > 0.0 => Occlusion::Transmissive,
+            true => Occlusion::Opaque,
+            false => Occlusion::None,
+        }
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.nodes.first().map(|node| node.bounds)
+    }
+}
@@ -0,0 +1,108 @@
+use std::time::{Duration, Instant};
+
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+
+// Render interactivo. Mientras la cámara se mueve se traza un píxel por bloque y
+// se rellena el bloque entero, ajustando el tamaño del bloque al presupuesto de
+// tiempo. Quieta, la imagen se refina en pasadas de bloques cada vez más chicos,
+// repartidas entre cuadros; al llegar a resolución completa se sigue
+// retrazando por filas para que las animaciones no se congelen.
+pub struct ProgressiveRenderer {
+    pub frame_budget: Duration,
+    pub max_step: usize,
+    motion_step: usize, // tamaño de bloque mientras hay movimiento
+    step: usize,        // tamaño de bloque de la pasada de refinamiento actual
+    next_row: usize,
+    skip_traced: bool,  // la pasada anterior ya trazó los píxeles en múltiplos de 2 * step
+}
+
+impl ProgressiveRenderer {
+    pub fn new(frame_budget: Duration) -> Self {
+        ProgressiveRenderer {
+            frame_budget,
+            max_step: 16,
+            motion_step: 4,
+            step: 16,
+            next_row: 0,
+            skip_traced: false,
+        }
+    }
+
+    // Ya se completó al menos una pasada a resolución completa desde el último movimiento
+    pub fn is_refined(&self) -> bool {
+        self.step == 1 && !self.skip_traced
+    }
+
+    // Vuelve a empezar desde la pasada más gruesa, p. ej. al cambiar la escena
+    pub fn restart(&mut self) {
+        self.step = self.max_step;
+        self.next_row = 0;
+        self.skip_traced = false;
+    }
+
+    // `shade` devuelve el color del píxel (x, y). Devuelve true si se terminó una
+    // pasada sobre toda la imagen; en movimiento, cada llamada es una.
+    pub fn render_frame(&mut self, framebuffer: &mut Framebuffer, moving: bool, mut shade: impl FnMut(usize, usize) -> Color) -> bool {
+        let start = Instant::now();
+
+        if moving {
+            let step = self.motion_step;
+            let rows = framebuffer.height;
+            trace_rows(framebuffer, step, false, 0..rows, &mut shade);
+
+            // Reducir el bloque a la mitad cuadruplica el costo, así que solo se
+            // baja si sobra bastante presupuesto
+            let elapsed = start.elapsed();
+            if elapsed > self.frame_budget && self.motion_step < self.max_step {
+                self.motion_step *= 2;
+            } else if elapsed * 4 < self.frame_budget && self.motion_step > 1 {
+                self.motion_step /= 2;
+            }
+
+            // El refinamiento sigue a partir de lo que se acaba de trazar
+            self.step = (step / 2).max(1);
+            self.skip_traced = step > 1;
+            self.next_row = 0;
+            return true;
+        }
+
+        let mut completed = false;
+        while start.elapsed() < self.frame_budget {
+            while self.next_row < framebuffer.height && start.elapsed() < self.frame_budget {
+                let row = self.next_row;
+                trace_rows(framebuffer, self.step, self.skip_traced, row..row + 1, &mut shade);
+                self.next_row += self.step;
+            }
+
+            if self.next_row < framebuffer.height {
+                break;
+            }
+            // Pasada terminada: la siguiente es más fina, o un retrazado completo
+            self.skip_traced = self.step > 1;
+            self.step = (self.step / 2).max(1);
+            self.next_row = 0;
+            completed = true;
+        }
+        completed
+    }
+}
+
+// Traza las filas de bloques que empiezan en `rows` (en píxeles, alineadas a `step`)
+fn trace_rows(
+    framebuffer: &mut Framebuffer,
+    step: usize,
+    skip_traced: bool,
+    rows: std::ops::Range<usize>,
+    shade: &mut impl FnMut(usize, usize) -> Color,
+) {
+    for y in rows.step_by(step) {
+        for x in (0..framebuffer.width).step_by(step) {
+            if skip_traced && x % (2 * step) == 0 && y % (2 * step) == 0 {
+                continue;
+            }
+            let color = shade(x, y);
+            framebuffer.fill_rect(x as isize, y as isize, step, step, color);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::cell::Cell;
+use nalgebra_glm::{Mat3, Vec3};
+
+// Rayo con su intervalo válido [t_min, t_max] y la inversa de la dirección
+// precalculada para las pruebas de losas
+#[derive(Debug, Clone, Copy)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+    pub t_min: f32,
+    pub t_max: f32,
+    pub time: f32,
+    pub inv_direction: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Ray {
+            origin,
+            direction,
+            t_min: 0.0,
+            t_max: f32::INFINITY,
+            time: 0.0,
+            inv_direction: Vec3::new(1.0 / direction.x, 1.0 / direction.y, 1.0 / direction.z),
+        }
+    }
+
+    pub fn with_range(mut self, t_min: f32, t_max: f32) -> Self {
+        self.t_min = t_min;
+        self.t_max = t_max;
+        self
+    }
+
+    pub fn with_time(mut self, time: f32) -> Self {
+        self.time = time;
+        self
+    }
+
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + self.direction * t
+    }
+
+    // El mismo rayo visto desde un sistema desplazado por `offset`
+    pub fn translated(&self, offset: &Vec3) -> Ray {
+        Ray {
+            origin: self.origin - offset,
+            ..*self
+        }
+    }
+
+    // El mismo rayo en un sistema con `inverse` (lineal) y trasladado por `offset`.
+    // La dirección no se normaliza, así que las distancias t valen en los dos sistemas.
+    pub fn transformed(&self, inverse: &Mat3, offset: &Vec3) -> Ray {
+        Ray::new(inverse * (self.origin - offset), inverse * self.direction)
+            .with_range(self.t_min, self.t_max)
+            .with_time(self.time)
+    }
+
+    // Rayo secundario que hereda el tiempo de este
+    pub fn spawn(&self, origin: Vec3, direction: Vec3) -> Ray {
+        Ray::new(origin, direction).with_time(self.time)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum RayKind {
+    Primary,
+    Secondary, // reflexión y refracción
+    Shadow,    // sombras, oclusión ambiental y rayos de luz en la niebla
+}
+
+// Rayos trazados por tipo, para las estadísticas del HUD
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RayCounts {
+    pub primary: u64,
+    pub secondary: u64,
+    pub shadow: u64,
+}
+
+impl RayCounts {
+    pub fn total(&self) -> u64 {
+        self.primary + self.secondary + self.shadow
+    }
+}
+
+impl std::ops::AddAssign for RayCounts {
+    fn add_assign(&mut self, other: RayCounts) {
+        self.primary += other.primary;
+        self.secondary += other.secondary;
+        self.shadow += other.shadow;
+    }
+}
+
+thread_local! {
+    static RAY_COUNTS: Cell<RayCounts> = const {
+        Cell::new(RayCounts { primary: 0, secondary: 0, shadow: 0 })
+    };
+}
+
+pub(crate) fn count_ray(kind: RayKind) {
+    RAY_COUNTS.with(|counts| {
+        let mut current = counts.get();
+        match kind {
+            RayKind::Primary => current.primary += 1,
+            RayKind::Secondary => current.secondary += 1,
+            RayKind::Shadow => current.shadow += 1,
+        }
+        counts.set(current);
+    });
+}
+
+// Devuelve los rayos contados en este hilo desde la última llamada y reinicia la cuenta
+#[cfg(feature = "window")]
+pub(crate) fn take_ray_counts() -> RayCounts {
+    RAY_COUNTS.with(|counts| counts.take())
+}
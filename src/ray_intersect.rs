@@ -1,39 +1,298 @@
 
-use nalgebra_glm::Vec3;
+use std::cell::Cell;
+use nalgebra_glm::{Mat3, Vec3};
 use crate::material::Material;
+use crate::ray::Ray;
 
+// Por debajo de esto se considera que el rayo es paralelo a una losa
+pub(crate) const PARALLEL_EPSILON: f32 = 1e-8;
+
+// Registro de una intersección. El material se toma prestado del objeto,
+// así procesar un impacto no copia ni reserva nada.
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
-pub struct Intersect {
+pub struct Hit<'a> {
     pub point: Vec3,
-    pub normal: Vec3,
+    pub normal: Vec3,     // Siempre hacia afuera del objeto
     pub distance: f32,
-    pub is_intersecting: bool,
-    pub material: Material,
-}
-
-impl Intersect {
-    pub fn new(point: Vec3, normal: Vec3, distance: f32, material: Material) -> Self {
-        Intersect {
-            point,
-            normal,
-            distance,
-            is_intersecting: true,
-            material,
+    pub front_face: bool, // false si el rayo sale del objeto (origen en su interior)
+    pub uv: (f32, f32),
+    pub object_id: u32,
+    pub material: &'a Material,
+}
+
+// Caja alineada a los ejes que envuelve un objeto
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Aabb { min, max }
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb::new(self.min.inf(&other.min), self.max.sup(&other.max))
+    }
+
+    pub fn translated(&self, offset: &Vec3) -> Aabb {
+        Aabb::new(self.min + offset, self.max + offset)
+    }
+
+    // Caja que envuelve a esta después de aplicarle `linear` y luego `offset`
+    pub fn transformed(&self, linear: &Mat3, offset: &Vec3) -> Aabb {
+        let corners = self.corners().map(|corner| linear * corner + offset);
+        corners[1..].iter().fold(Aabb::new(corners[0], corners[0]), |bounds, corner| {
+            Aabb::new(bounds.min.inf(corner), bounds.max.sup(corner))
+        })
+    }
+
+    // Método de losas: intervalo (t_near, eje, t_far, eje) en que el rayo está
+    // dentro de las tres losas, con el eje que definió cada extremo para sacar la
+    // normal. No se recorta a [t_min, t_max] del rayo; None si no toca la caja.
+    pub fn slab(&self, ray: &Ray) -> Option<(f32, usize, f32, usize)> {
+        if !ray.origin.iter().chain(ray.direction.iter()).all(|c| c.is_finite()) {
+            return None;
+        }
+
+        let mut t_near = f32::NEG_INFINITY;
+        let mut t_far = f32::INFINITY;
+        let mut near_axis = 0;
+        let mut far_axis = 0;
+
+        for axis in 0..3 {
+            let origin = ray.origin[axis];
+
+            if ray.direction[axis].abs() < PARALLEL_EPSILON {
+                // Rayo paralelo a la losa: o está entre sus planos o no la toca nunca
+                if origin < self.min[axis] || origin > self.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+
+            let mut t0 = (self.min[axis] - origin) * ray.inv_direction[axis];
+            let mut t1 = (self.max[axis] - origin) * ray.inv_direction[axis];
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            if t0 > t_near {
+                t_near = t0;
+                near_axis = axis;
+            }
+            if t1 < t_far {
+                t_far = t1;
+                far_axis = axis;
+            }
+
+            if t_near > t_far {
+                return None;
+            }
         }
+
+        // Con la dirección nula ninguna losa acota el intervalo
+        t_far.is_finite().then_some((t_near, near_axis, t_far, far_axis))
+    }
+
+    // Coordenadas UV, de 0 a 1, del punto dentro de la cara perpendicular a `axis`
+    pub fn face_uv(&self, point: &Vec3, axis: usize) -> (f32, f32) {
+        let (u_axis, v_axis) = face_axes(axis);
+        let size = self.max - self.min;
+        (
+            ((point[u_axis] - self.min[u_axis]) / size[u_axis]).clamp(0.0, 1.0),
+            ((point[v_axis] - self.min[v_axis]) / size[v_axis]).clamp(0.0, 1.0),
+        )
+    }
+
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (a, b) = (self.min, self.max);
+        [
+            Vec3::new(a.x, a.y, a.z),
+            Vec3::new(b.x, a.y, a.z),
+            Vec3::new(b.x, b.y, a.z),
+            Vec3::new(a.x, b.y, a.z),
+            Vec3::new(a.x, a.y, b.z),
+            Vec3::new(b.x, a.y, b.z),
+            Vec3::new(b.x, b.y, b.z),
+            Vec3::new(a.x, b.y, b.z),
+        ]
     }
+}
+
+// Ejes que hacen de U y V en una cara perpendicular a `axis`
+pub fn face_axes(axis: usize) -> (usize, usize) {
+    match axis {
+        0 => (2, 1),
+        1 => (0, 2),
+        _ => (0, 1),
+    }
+}
 
-    pub fn empty() -> Self {
-        Intersect {
-            point: Vec3::zeros(),
-            normal: Vec3::zeros(),
-            distance: 0.0,
-            is_intersecting: false,
-            material: Material::black(),
+// Resultado de una consulta de oclusión, ordenado de menor a mayor bloqueo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Occlusion {
+    None,
+    Transmissive, // Solo lo cruzan materiales transparentes
+    Opaque,
+}
+
+// Send + Sync para que la escena pueda vivir en el hilo de render
+pub trait RayIntersect: Send + Sync {
+    // Impacto más cercano dentro de [ray.t_min, ray.t_max]
+    fn ray_intersect(&self, ray: &Ray) -> Option<Hit<'_>>;
+
+    // Consulta para rayos de sombra: ¿hay algo antes de ray.t_max?
+    // No construye el Hit y termina en cuanto encuentra un objeto opaco.
+    fn occlusion(&self, ray: &Ray) -> Occlusion;
+
+    // Avanza los objetos animados al tiempo dado (en segundos); los estáticos lo ignoran
+    fn update(&mut self, _time: f32) {}
+
+    // Caja envolvente en espacio de mundo, en el tiempo actual
+    fn bounds(&self) -> Option<Aabb>;
+}
+
+// Impacto más cercano de una lista de objetos, acortando el rayo con cada uno
+pub fn closest_hit<'a>(objects: &'a [Box<dyn RayIntersect>], ray: &Ray) -> Option<Hit<'a>> {
+    let mut ray = *ray;
+    let mut closest = None;
+
+    for object in objects {
+        if let Some(hit) = object.ray_intersect(&ray) {
+            ray.t_max = hit.distance;
+            closest = Some(hit);
         }
     }
+
+    closest
 }
 
-pub trait RayIntersect {
-    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect;
-}
\ No newline at end of file
+// Caja que envuelve a todos los objetos de la lista
+pub fn bounds_of(objects: &[Box<dyn RayIntersect>]) -> Option<Aabb> {
+    objects
+        .iter()
+        .filter_map(|object| object.bounds())
+        .reduce(|a, b| a.union(&b))
+}
+
+// Oclusión combinada de una lista de objetos, con salida temprana al primer opaco
+pub fn any_occlusion(objects: &[Box<dyn RayIntersect>], ray: &Ray) -> Occlusion {
+    let mut occlusion = Occlusion::None;
+
+    for object in objects {
+        occlusion = occlusion.max(object.occlusion(ray));
+        if occlusion == Occlusion::Opaque {
+            break;
+        }
+    }
+
+    occlusion
+}
+
+thread_local! {
+    // Pruebas rayo-primitiva hechas en este hilo, para el mapa de calor de depuración
+    static INTERSECTION_TESTS: Cell<u32> = const { Cell::new(0) };
+}
+
+pub(crate) fn count_intersection_test() {
+    INTERSECTION_TESTS.with(|tests| tests.set(tests.get() + 1));
+}
+
+// Devuelve las pruebas contadas desde la última llamada y reinicia el contador
+#[cfg(feature = "window")]
+pub(crate) fn take_intersection_tests() -> u32 {
+    INTERSECTION_TESTS.with(|tests| tests.replace(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::cube::Cube;
+
+    fn unit_box() -> Aabb {
+        Aabb::new(Vec3::zeros(), Vec3::new(1.0, 1.0, 1.0))
+    }
+
+    fn unit_cube() -> Cube {
+        Cube::new(Vec3::zeros(), Vec3::new(1.0, 1.0, 1.0), Arc::new(Material::black()))
+    }
+
+    fn along_x(origin: Vec3) -> Ray {
+        Ray::new(origin, Vec3::new(1.0, 0.0, 0.0))
+    }
+
+    #[test]
+    fn entra_por_la_cara_cercana() {
+        assert_eq!(unit_box().slab(&along_x(Vec3::new(-2.0, 0.5, 0.5))), Some((2.0, 0, 3.0, 0)));
+
+        let cube = unit_cube();
+        let hit = cube.ray_intersect(&along_x(Vec3::new(-2.0, 0.5, 0.5))).unwrap();
+        assert_eq!(hit.distance, 2.0);
+        assert_eq!(hit.normal, Vec3::new(-1.0, 0.0, 0.0));
+        assert!(hit.front_face);
+    }
+
+    #[test]
+    fn desde_adentro_sale_por_la_cara_lejana() {
+        let ray = Ray::new(Vec3::new(0.5, 0.5, 0.5), Vec3::new(0.0, -1.0, 0.0));
+        assert_eq!(unit_box().slab(&ray), Some((-0.5, 1, 0.5, 1)));
+
+        let cube = unit_cube();
+        let hit = cube.ray_intersect(&ray).unwrap();
+        assert_eq!(hit.distance, 0.5);
+        // La normal sigue apuntando hacia afuera del cubo
+        assert_eq!(hit.normal, Vec3::new(0.0, -1.0, 0.0));
+        assert!(!hit.front_face);
+    }
+
+    #[test]
+    fn rayos_paralelos_a_una_losa() {
+        // Dentro de las losas de Y y Z, incluso sobre el borde
+        assert!(unit_box().slab(&along_x(Vec3::new(-1.0, 0.5, 0.5))).is_some());
+        assert!(unit_box().slab(&along_x(Vec3::new(-1.0, 1.0, 0.0))).is_some());
+        // Fuera de la losa de Y no la toca nunca, vaya para donde vaya en X
+        assert_eq!(unit_box().slab(&along_x(Vec3::new(-1.0, 1.5, 0.5))), None);
+        assert_eq!(unit_box().slab(&along_x(Vec3::new(0.5, -0.1, 0.5))), None);
+        assert!(unit_cube().ray_intersect(&along_x(Vec3::new(0.5, 1.5, 0.5))).is_none());
+    }
+
+    #[test]
+    fn direcciones_no_finitas_no_chocan() {
+        let origin = Vec3::new(-1.0, 0.5, 0.5);
+        for direction in [
+            Vec3::new(f32::NAN, 0.0, 0.0),
+            Vec3::new(1.0, f32::NAN, 0.0),
+            Vec3::new(f32::INFINITY, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, f32::NEG_INFINITY),
+            Vec3::zeros(),
+        ] {
+            let ray = Ray::new(origin, direction);
+            assert_eq!(unit_box().slab(&ray), None, "{:?}", direction);
+            assert!(unit_cube().ray_intersect(&ray).is_none(), "{:?}", direction);
+        }
+        assert_eq!(unit_box().slab(&along_x(Vec3::new(f32::NAN, 0.5, 0.5))), None);
+    }
+
+    #[test]
+    fn respeta_el_intervalo_del_rayo() {
+        let cube = unit_cube();
+        let ray = along_x(Vec3::new(-2.0, 0.5, 0.5));
+
+        // La caja queda toda después de t_max
+        assert!(cube.ray_intersect(&ray.with_range(0.0, 1.5)).is_none());
+        assert_eq!(cube.occlusion(&ray.with_range(0.0, 1.5)), Occlusion::None);
+        assert_eq!(cube.occlusion(&ray.with_range(0.0, 2.5)), Occlusion::Opaque);
+
+        // Con t_min dentro de la caja el impacto es la salida
+        let hit = cube.ray_intersect(&ray.with_range(2.5, 10.0)).unwrap();
+        assert_eq!(hit.distance, 3.0);
+        assert!(!hit.front_face);
+
+        // Con t_min después de la caja, o la caja detrás del origen, no hay impacto
+        assert!(cube.ray_intersect(&ray.with_range(3.5, 10.0)).is_none());
+        assert!(cube.ray_intersect(&along_x(Vec3::new(2.0, 0.5, 0.5))).is_none());
+    }
+}
@@ -0,0 +1,371 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use nalgebra_glm::Vec3;
+
+use crate::camera::Camera;
+use crate::debug_view::{DebugView, render_debug};
+use crate::denoise::Denoiser;
+use crate::framebuffer::Framebuffer;
+use crate::gizmos::draw_gizmos;
+use crate::progressive::ProgressiveRenderer;
+use crate::ray::{RayCounts, take_ray_counts};
+use crate::renderer::{RenderSettings, render_rows, trace_pixel};
+use crate::scene::Scene;
+
+// Cada cuánto se publica un cuadro a medio terminar en los modos de cuadro completo
+const PARTIAL_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+const PROGRESSIVE_BUDGET: Duration = Duration::from_millis(30);
+
+// Cómo se quiere ver la escena; cambiar cualquiera de estos reinicia el cuadro
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewSettings {
+    pub debug_view: Option<DebugView>,
+    pub denoise: bool,
+    pub interactive: bool, // refinamiento progresivo en vez de cuadros completos
+    pub gizmos: bool,
+}
+
+// Lo que la interfaz le pide al hilo de render. `generation` la sube la interfaz
+// en los cambios discretos de la escena (saltos de tiempo, pausa), que mueven
+// las luces y deben cancelar el cuadro en curso aunque la cámara esté quieta.
+#[derive(Debug, Clone)]
+pub struct RenderRequest {
+    pub camera: Camera,
+    pub time: f32,
+    pub view: ViewSettings,
+    pub generation: u64,
+}
+
+impl RenderRequest {
+    fn invalidates(&self, other: &RenderRequest) -> bool {
+        self.camera != other.camera || self.view != other.view || self.generation != other.generation
+    }
+}
+
+// Rayos y tiempo de render de un cuadro entero, sumando todos sus tramos. En el
+// modo progresivo un cuadro es una pasada sobre toda la imagen.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameTotals {
+    pub rays: RayCounts,
+    pub render_time: Duration,
+}
+
+impl FrameTotals {
+    fn add(&mut self, rays: RayCounts, render_time: Duration) {
+        self.rays += rays;
+        self.render_time += render_time;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FrameStats {
+    pub finished: bool, // false si es una versión parcial del cuadro
+    pub time: f32,
+    pub day_fraction: f32,
+    pub sun: Option<Vec3>, // None si la escena no tiene sol
+    pub last_frame: Option<FrameTotals>, // el último cuadro terminado; None hasta el primero
+}
+
+// Lo que viaja por el canal hacia el hilo de render
+enum Message {
+    Render(RenderRequest),
+    Scene(Box<Scene>), // reemplaza la escena (recarga en caliente)
+}
+
+pub struct PublishedFrame {
+    pub buffer: Vec<u32>,
+    pub stats: FrameStats,
+}
+
+// Render en un hilo aparte: la interfaz manda pedidos sin bloquearse y toma el
+// último cuadro publicado cuando quiere mostrar algo.
+pub struct RenderThread {
+    requests: Option<Sender<Message>>,
+    latest_frame: Arc<Mutex<Option<PublishedFrame>>>,
+    cancel: Arc<AtomicBool>,
+    last_request: Option<RenderRequest>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl RenderThread {
+    // El tamaño del cuadro y el denoiser de la tecla D salen de `settings`
+    pub fn spawn(scene: Scene, settings: RenderSettings) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let latest_frame = Arc::new(Mutex::new(None));
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let worker = Worker {
+            scene,
+            framebuffer: Framebuffer::new(settings.width, settings.height),
+            display: Framebuffer::new(settings.width, settings.height),
+            progressive: ProgressiveRenderer::new(PROGRESSIVE_BUDGET),
+            progressive_pass: FrameTotals::default(),
+            last_frame: None,
+            denoiser: settings.denoiser.unwrap_or(Denoiser::new(1.0)),
+            settings,
+            requests: receiver,
+            latest_frame: latest_frame.clone(),
+            cancel: cancel.clone(),
+        };
+        let handle = thread::spawn(move || worker.run());
+
+        RenderThread {
+            requests: Some(sender),
+            latest_frame,
+            cancel,
+            last_request: None,
+            handle: Some(handle),
+        }
+    }
+
+    // Si el pedido cambia la cámara, la vista o la escena, el cuadro en curso se cancela
+    pub fn request(&mut self, request: RenderRequest) {
+        if self.last_request.as_ref().is_some_and(|last| last.invalidates(&request)) {
+            self.cancel.store(true, Ordering::Relaxed);
+        }
+        if let Some(sender) = &self.requests {
+            // Si el hilo murió no hay nada que hacer; la ventana se queda con el último cuadro
+            let _ = sender.send(Message::Render(request.clone()));
+        }
+        self.last_request = Some(request);
+    }
+
+    // Cambia la escena que se renderiza; el cuadro en curso se descarta
+    pub fn replace_scene(&mut self, scene: Scene) {
+        self.cancel.store(true, Ordering::Relaxed);
+        if let Some(sender) = &self.requests {
+            let _ = sender.send(Message::Scene(Box::new(scene)));
+        }
+    }
+
+    // Último cuadro publicado desde la llamada anterior, si hay uno nuevo
+    pub fn take_frame(&self) -> Option<PublishedFrame> {
+        self.latest_frame.lock().ok()?.take()
+    }
+}
+
+impl Drop for RenderThread {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        // Cerrar el canal hace que el hilo termine su bucle
+        self.requests = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+struct Worker {
+    scene: Scene,
+    settings: RenderSettings,
+    framebuffer: Framebuffer, // donde se traza
+    display: Framebuffer,     // copia con los gizmos encima, la que se publica
+    progressive: ProgressiveRenderer,
+    progressive_pass: FrameTotals, // lo que va de la pasada progresiva en curso
+    last_frame: Option<FrameTotals>,
+    denoiser: Denoiser,
+    requests: Receiver<Message>,
+    latest_frame: Arc<Mutex<Option<PublishedFrame>>>,
+    cancel: Arc<AtomicBool>,
+}
+
+// Estado del cuadro completo en curso
+struct FullFrame {
+    next_row: usize,
+    time: f32,
+    totals: FrameTotals,
+}
+
+impl Worker {
+    fn run(mut self) {
+        let mut current: Option<RenderRequest> = None;
+        let mut full_frame: Option<FullFrame> = None;
+        let mut previous_camera: Option<Camera> = None;
+        let mut rendered_time: Option<f32> = None;
+
+        loop {
+            // Sin trabajo pendiente se bloquea hasta el próximo pedido; si no, solo
+            // revisa si llegaron pedidos nuevos y se queda con el más reciente
+            let idle = current.as_ref().is_some_and(|request| {
+                full_frame.is_none() && rendered_time == Some(request.time) && self.is_done(request)
+            });
+            // Se limpia antes de leer el canal: una cancelación posterior siempre
+            // viene con un pedido que se leerá en esta vuelta o en la siguiente
+            self.cancel.store(false, Ordering::Relaxed);
+            let mut latest = None;
+            let mut new_scene = None;
+            let mut next_message = if idle || current.is_none() {
+                match self.requests.recv() {
+                    Ok(message) => Some(message),
+                    Err(_) => return,
+                }
+            } else {
+                None
+            };
+            loop {
+                match next_message.take() {
+                    Some(Message::Render(request)) => latest = Some(request),
+                    Some(Message::Scene(scene)) => new_scene = Some(scene),
+                    None => {}
+                }
+                match self.requests.try_recv() {
+                    Ok(message) => next_message = Some(message),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return,
+                }
+            }
+
+            if let Some(scene) = new_scene {
+                self.scene = *scene;
+                full_frame = None;
+                rendered_time = None;
+                self.restart_progressive();
+            }
+            if let Some(request) = latest {
+                if current.as_ref().is_none_or(|current| current.invalidates(&request)) {
+                    full_frame = None;
+                    rendered_time = None;
+                    match &current {
+                        Some(current) if current.view == request.view => {
+                            // Con la cámara quieta el refinamiento no se reiniciaría solo
+                            if current.camera == request.camera {
+                                self.restart_progressive();
+                            }
+                        }
+                        _ => self.apply_view(&request.view),
+                    }
+                }
+                current = Some(request);
+            }
+            let Some(request) = current.clone() else {
+                continue;
+            };
+            // El pedido puede ser igual al cuadro ya terminado
+            if full_frame.is_none() && rendered_time == Some(request.time) && self.is_done(&request) {
+                continue;
+            }
+
+            if let Some(view) = request.view.debug_view {
+                if self.render_debug(&request, view) {
+                    rendered_time = Some(request.time);
+                }
+            } else if request.view.interactive && !request.view.denoise {
+                let moving = previous_camera.as_ref() != Some(&request.camera);
+                previous_camera = Some(request.camera.clone());
+                self.render_progressive(&request, moving);
+                rendered_time = Some(request.time);
+            } else {
+                let frame = full_frame.get_or_insert_with(|| {
+                    take_ray_counts();
+                    FullFrame { next_row: 0, time: request.time, totals: FrameTotals::default() }
+                });
+                if self.render_full_slice(&request, frame) {
+                    rendered_time = Some(frame.time);
+                    full_frame = None;
+                }
+            }
+        }
+    }
+
+    fn is_done(&self, request: &RenderRequest) -> bool {
+        if request.view.interactive && !request.view.denoise && request.view.debug_view.is_none() {
+            self.progressive.is_refined()
+        } else {
+            true
+        }
+    }
+
+    fn apply_view(&mut self, view: &ViewSettings) {
+        if view.denoise && view.debug_view.is_none() {
+            self.framebuffer.enable_aovs(&Denoiser::GUIDES);
+        } else {
+            self.framebuffer.aovs = None;
+        }
+        self.restart_progressive();
+    }
+
+    fn restart_progressive(&mut self) {
+        self.progressive.restart();
+        self.progressive_pass = FrameTotals::default();
+    }
+
+    fn render_debug(&mut self, request: &RenderRequest, view: DebugView) -> bool {
+        let started = Instant::now();
+        take_ray_counts();
+        self.scene.set_time(request.time);
+        let finished = render_debug(&mut self.framebuffer, &self.scene, &request.camera, &self.settings, view, &self.cancel);
+        if finished {
+            self.last_frame = Some(FrameTotals { rays: take_ray_counts(), render_time: started.elapsed() });
+            self.publish(request, true, request.time);
+        }
+        finished
+    }
+
+    fn render_progressive(&mut self, request: &RenderRequest, moving: bool) {
+        let started = Instant::now();
+        take_ray_counts();
+        self.scene.set_time(request.time);
+
+        let (scene, settings, camera) = (&self.scene, &self.settings, &request.camera);
+        let completed = self.progressive.render_frame(&mut self.framebuffer, moving, |x, y| {
+            trace_pixel(scene, camera, settings, x, y, None)
+        });
+
+        // Un llamado que cierra una pasada se le cuenta entero a esa pasada
+        self.progressive_pass.add(take_ray_counts(), started.elapsed());
+        if completed {
+            self.last_frame = Some(std::mem::take(&mut self.progressive_pass));
+        }
+        let finished = self.progressive.is_refined();
+        self.publish(request, finished, request.time);
+    }
+
+    // Avanza el cuadro completo unas filas; devuelve true al terminarlo
+    fn render_full_slice(&mut self, request: &RenderRequest, frame: &mut FullFrame) -> bool {
+        if frame.next_row == 0 {
+            self.scene.set_time(frame.time);
+        }
+
+        let slice_start = Instant::now();
+        while frame.next_row < self.framebuffer.height && slice_start.elapsed() < PARTIAL_FRAME_INTERVAL {
+            if self.cancel.load(Ordering::Relaxed) {
+                return false;
+            }
+            render_rows(&mut self.framebuffer, &self.scene, &request.camera, &self.settings, frame.next_row..frame.next_row + 1);
+            frame.next_row += 1;
+        }
+
+        let finished = frame.next_row >= self.framebuffer.height;
+        if finished && request.view.denoise {
+            self.denoiser.apply(&mut self.framebuffer);
+        }
+        frame.totals.add(take_ray_counts(), slice_start.elapsed());
+        if finished {
+            self.last_frame = Some(frame.totals);
+        }
+        self.publish(request, finished, frame.time);
+        finished
+    }
+
+    fn publish(&mut self, request: &RenderRequest, finished: bool, time: f32) {
+        self.display.buffer.copy_from_slice(&self.framebuffer.buffer);
+        if request.view.gizmos {
+            draw_gizmos(&mut self.display, &self.scene, &request.camera);
+        }
+
+        let stats = FrameStats {
+            finished,
+            time,
+            day_fraction: self.scene.day_cycle.day_fraction(time),
+            sun: self.scene.sun().map(|sun| sun.position),
+            last_frame: self.last_frame,
+        };
+        if let Ok(mut latest) = self.latest_frame.lock() {
+            *latest = Some(PublishedFrame { buffer: self.display.buffer.clone(), stats });
+        }
+    }
+}
@@ -0,0 +1,380 @@
+use nalgebra_glm::{Vec3, normalize};
+use std::ops::Range;
+
+use crate::aov::{Aov, AovSample};
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::denoise::Denoiser;
+use crate::framebuffer::Framebuffer;
+use crate::light::Light;
+use crate::media::Fog;
+use crate::ray::{Ray, RayKind, count_ray};
+use crate::ray_intersect::{Hit, Occlusion, RayIntersect, closest_hit, any_occlusion};
+use crate::sampling::Sampler;
+use crate::scene::Scene;
+
+const MAX_SHADOW_LAYERS: u32 = 8;
+
+// Parámetros del render. Los de la imagen (tamaño, muestras, AOVs, denoiser) y
+// los del trazado en sí, que antes eran constantes sueltas.
+#[derive(Debug, Clone)]
+pub struct RenderSettings {
+    pub width: usize,
+    pub height: usize,
+    pub samples: u32,                // rayos primarios por píxel
+    pub max_depth: u32,              // rebotes de reflexión y refracción
+    pub origin_bias: f32,            // separación de los rayos secundarios respecto a la superficie
+    pub sky_color: Color,            // lo que ven los rayos que no chocan con nada
+    pub ao_samples: Option<u32>,     // rayos de oclusión ambiental; None = los de la escena
+    pub ao_radius: Option<f32>,      // alcance de esos rayos; None = el de la escena
+    pub aovs: Vec<Aov>,              // buffers auxiliares que se llenan en cada cuadro
+    pub denoiser: Option<Denoiser>,  // se aplica al terminar cada cuadro
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            width: 800,
+            height: 600,
+            samples: 1,
+            max_depth: 3,
+            origin_bias: 1e-4,
+            sky_color: Color::new(102, 153, 255),
+            ao_samples: None,
+            ao_radius: None,
+            aovs: Vec::new(),
+            denoiser: None,
+        }
+    }
+}
+
+fn offset_origin(hit: &Hit, direction: &Vec3, bias: f32) -> Vec3 {
+    let offset = hit.normal * bias;
+    if direction.dot(&hit.normal) < 0.0 {
+        hit.point - offset
+    } else {
+        hit.point + offset
+    }
+}
+
+fn reflect(incident: &Vec3, normal: &Vec3) -> Vec3 {
+    incident - 2.0 * incident.dot(normal) * normal
+}
+
+fn refract(incident: &Vec3, normal: &Vec3, eta_t: f32) -> Vec3 {
+    let cosi = -incident.dot(normal).clamp(-1.0, 1.0);
+    
+    let (n_cosi, eta, n_normal);
+
+    // cosi < 0: el rayo sale del objeto (va a favor de la normal exterior)
+    if cosi < 0.0 {
+        n_cosi = -cosi;
+        eta = eta_t;
+        n_normal = -normal;
+    } else {
+        n_cosi = cosi;
+        eta = 1.0 / eta_t;
+        n_normal = *normal;
+    }
+    
+    let k = 1.0 - eta * eta * (1.0 - n_cosi * n_cosi);
+    
+    if k < 0.0 {
+        reflect(incident, &n_normal)
+    } else {
+        eta * incident + (eta * n_cosi - k.sqrt()) * n_normal
+    }
+}
+
+// Sigue el rayo de sombra a través de los materiales transparentes, acumulando
+// su transmitancia y su tinte. Devuelve el filtro que se aplica al color de la luz:
+// blanco = sin sombra, negro = bloqueada por un objeto opaco.
+fn cast_shadow(
+    hit: &Hit,
+    light: &Light,
+    objects: &[Box<dyn RayIntersect>],
+    time: f32,
+    bias: f32,
+) -> Color {
+    let light_dir = (light.position - hit.point).normalize();
+    let light_distance = (light.position - hit.point).magnitude();
+
+    let mut shadow_ray = Ray::new(offset_origin(hit, &light_dir, bias), light_dir)
+        .with_range(0.0, light_distance)
+        .with_time(time);
+    let mut transmittance = Color::new(255, 255, 255);
+
+    count_ray(RayKind::Shadow);
+    match any_occlusion(objects, &shadow_ray) {
+        Occlusion::None => return transmittance,
+        Occlusion::Opaque => return Color::black(),
+        Occlusion::Transmissive => {}
+    }
+
+    for _ in 0..MAX_SHADOW_LAYERS {
+        let Some(occluder) = closest_hit(objects, &shadow_ray) else {
+            return transmittance;
+        };
+
+        let transparency = occluder.material.albedo[3];
+        if transparency <= 0.0 {
+            return Color::black();
+        }
+
+        // Solo se tiñe al entrar al objeto, no al salir por la cara opuesta
+        if occluder.front_face {
+            let tint = Color::new(255, 255, 255).interpolate(&occluder.material.diffuse, 1.0 - transparency);
+            transmittance = transmittance * tint * transparency;
+        }
+
+        count_ray(RayKind::Shadow);
+        let remaining_distance = shadow_ray.t_max - occluder.distance;
+        shadow_ray = shadow_ray
+            .spawn(offset_origin(&occluder, &light_dir, bias), light_dir)
+            .with_range(0.0, remaining_distance);
+    }
+
+    // Se acabaron las capas: lo que quede del tramo no se tiñe, pero un objeto
+    // opaco más allá igual tiene que dar sombra
+    count_ray(RayKind::Shadow);
+    match any_occlusion(objects, &shadow_ray) {
+        Occlusion::Opaque => Color::black(),
+        _ => transmittance,
+    }
+}
+
+
+// `aov` recibe los datos del impacto para los buffers auxiliares; solo se pasa
+// en el rayo primario.
+pub(crate) fn cast_ray(
+    ray: &Ray,
+    scene: &Scene,
+    settings: &RenderSettings,
+    sampler: &mut Sampler,
+    depth: u32,
+    aov: Option<&mut AovSample>,
+) -> Color {
+    if depth > settings.max_depth {
+        return settings.sky_color;
+    }
+
+    count_ray(if depth == 0 { RayKind::Primary } else { RayKind::Secondary });
+
+    let Scene { objects, lights, ambient, fog, .. } = scene;
+
+    let Some(hit) = closest_hit(objects, ray) else {
+        let sky = fog.apply(settings.sky_color, ray, None) + light_shafts(ray, None, objects, scene.sun(), fog, sampler, depth);
+        if let Some(aov) = aov {
+            aov.direct = sky;
+        }
+        return sky;
+    };
+
+    let view_dir = (ray.origin - hit.point).normalize();
+    let surface_color = hit.material.shade(hit.uv);
+
+    // La oclusión ambiental solo se traza en el impacto primario; en reflejos y
+    // refracciones basta con la luz ambiental sin ocluir
+    let ambient_visibility = if depth == 0 {
+        ambient.visibility(&hit, settings, objects, sampler)
+    } else {
+        1.0
+    };
+
+    let mut diffuse = ambient.shade(surface_color, ambient_visibility) * hit.material.albedo[0];
+    let mut specular = Color::black();
+
+    for light in lights {
+        let light_dir = (light.position - hit.point).normalize();
+        let reflect_dir = reflect(&-light_dir, &hit.normal).normalize();
+
+        let light_color = light.color * cast_shadow(&hit, light, objects, ray.time, settings.origin_bias);
+        let light_intensity = light.intensity_toward(&hit.point);
+
+        let diffuse_intensity = hit.normal.dot(&light_dir).clamp(0.0, 1.0);
+        diffuse = diffuse + surface_color * light_color * hit.material.albedo[0] * diffuse_intensity * light_intensity;
+
+        let specular_intensity = view_dir.dot(&reflect_dir).max(0.0).powf(hit.material.specular);
+        specular = specular + light_color * hit.material.albedo[1] * specular_intensity * light_intensity;
+    }
+
+    let mut reflect_color = Color::black();
+    let reflectivity = hit.material.albedo[2];
+    if reflectivity > 0.0 {
+        let reflect_dir = reflect(&ray.direction, &hit.normal).normalize();
+        let reflect_ray = ray.spawn(offset_origin(&hit, &reflect_dir, settings.origin_bias), reflect_dir);
+        reflect_color = cast_ray(&reflect_ray, scene, settings, sampler, depth + 1, None);
+    }
+
+
+    let mut refract_color = Color::black();
+    let transparency = hit.material.albedo[3];
+    if transparency > 0.0 {
+        let refract_dir = refract(&ray.direction, &hit.normal, hit.material.refractive_index);
+        let refract_ray = ray.spawn(offset_origin(&hit, &refract_dir, settings.origin_bias), refract_dir);
+        refract_color = cast_ray(&refract_ray, scene, settings, sampler, depth + 1, None);
+    }
+
+    let direct = (diffuse + specular) * (1.0 - reflectivity - transparency) + hit.material.emitted();
+    let reflection = reflect_color * reflectivity;
+    let refraction = refract_color * transparency;
+    let color = direct + reflection + refraction;
+
+    if let Some(aov) = aov {
+        *aov = AovSample {
+            depth: hit.distance,
+            normal: hit.normal,
+            albedo: surface_color,
+            position: hit.point,
+            uv: hit.uv,
+            object_id: hit.object_id,
+            material_id: hit.material.id,
+            direct,
+            reflection,
+            refraction,
+        };
+    }
+
+    // Si el rayo viajaba por dentro de un objeto con volumen, lo atenúa ese medio;
+    // si no, la niebla del aire
+    match hit.material.medium {
+        Some(medium) if !hit.front_face => medium.apply(color, hit.distance),
+        _ => fog.apply(color, ray, Some(hit.distance)) + light_shafts(ray, Some(hit.distance), objects, scene.sun(), fog, sampler, depth),
+    }
+}
+
+// Rayos de luz del sol, solo para los rayos primarios
+fn light_shafts(
+    ray: &Ray,
+    distance: Option<f32>,
+    objects: &[Box<dyn RayIntersect>],
+    sun: Option<&Light>,
+    fog: &Fog,
+    sampler: &mut Sampler,
+    depth: u32,
+) -> Color {
+    match sun {
+        Some(sun) if depth == 0 => fog.light_shafts(ray, distance, sun, objects, sampler),
+        _ => Color::black(),
+    }
+}
+
+// Rayo primario que pasa por el punto (x, y) de la imagen; la esquina superior
+// izquierda de cada píxel está en sus coordenadas enteras
+pub(crate) fn primary_ray(camera: &Camera, x: f32, y: f32, width: usize, height: usize) -> Ray {
+    let width = width as f32;
+    let height = height as f32;
+    let aspect_ratio = width / height;
+    let perspective_scale = (camera.fov * 0.5).tan();
+
+    let screen_x = (2.0 * x) / width - 1.0;
+    let screen_y = -(2.0 * y) / height + 1.0;
+
+    let screen_x = screen_x * aspect_ratio * perspective_scale;
+    let screen_y = screen_y * perspective_scale;
+
+    let ray_direction = normalize(&Vec3::new(screen_x, screen_y, -1.0));
+
+    let rotated_direction = camera.base_change(&ray_direction);
+
+    Ray::new(camera.eye, rotated_direction)
+}
+
+// Color del píxel (x, y) de una imagen de settings.width x settings.height. La
+// primera muestra va a la esquina del píxel y las demás a puntos al azar dentro
+// de él; `aov` se llena con la primera.
+pub(crate) fn trace_pixel(
+    scene: &Scene,
+    camera: &Camera,
+    settings: &RenderSettings,
+    x: usize,
+    y: usize,
+    mut aov: Option<&mut AovSample>,
+) -> Color {
+    let samples = settings.samples.max(1);
+    let mut colors = Vec::with_capacity(samples as usize);
+
+    for sample in 0..samples {
+        let mut sampler = Sampler::for_pixel(x, y, sample);
+        let (dx, dy) = if sample == 0 { (0.0, 0.0) } else { (sampler.next_f32(), sampler.next_f32()) };
+        let ray = primary_ray(camera, x as f32 + dx, y as f32 + dy, settings.width, settings.height);
+
+        let color = match aov.take() {
+            Some(aov) => {
+                let color = cast_ray(&ray, scene, settings, &mut sampler, 0, Some(&mut *aov));
+                // Profundidad lineal: distancia proyectada sobre el eje de la cámara
+                let forward = (camera.center - camera.eye).normalize();
+                aov.depth *= ray.direction.dot(&forward);
+                color
+            }
+            None => cast_ray(&ray, scene, settings, &mut sampler, 0, None),
+        };
+        colors.push(color);
+    }
+
+    Color::average(&colors)
+}
+
+pub(crate) fn render(framebuffer: &mut Framebuffer, scene: &Scene, camera: &Camera, settings: &RenderSettings) {
+    let height = framebuffer.height;
+    render_rows(framebuffer, scene, camera, settings, 0..height);
+}
+
+// Solo las filas dadas, para poder repartir un cuadro en partes. El framebuffer
+// debe tener el tamaño de `settings`.
+pub(crate) fn render_rows(framebuffer: &mut Framebuffer, scene: &Scene, camera: &Camera, settings: &RenderSettings, rows: Range<usize>) {
+    for y in rows.start..rows.end.min(framebuffer.height) {
+        for x in 0..framebuffer.width {
+            let pixel_color = match framebuffer.aovs.as_mut() {
+                Some(aovs) => {
+                    let mut sample = AovSample::default();
+                    let color = trace_pixel(scene, camera, settings, x, y, Some(&mut sample));
+                    aovs.store(x, y, &sample);
+                    color
+                }
+                None => trace_pixel(scene, camera, settings, x, y, None),
+            };
+
+            framebuffer.point(x, y, pixel_color);
+        }
+    }
+}
+
+// Punto de entrada para quien use el trazador como biblioteca: dueño del
+// framebuffer, renderiza cuadros completos de una escena ya preparada.
+pub struct Renderer {
+    settings: RenderSettings,
+    framebuffer: Framebuffer,
+}
+
+impl Renderer {
+    pub fn new(settings: RenderSettings) -> Self {
+        let mut framebuffer = Framebuffer::new(settings.width, settings.height);
+        // El denoiser necesita sus buffers guía aunque no se hayan pedido AOVs
+        let mut aovs = settings.aovs.clone();
+        if settings.denoiser.is_some() {
+            aovs.extend(Denoiser::GUIDES);
+        }
+        if !aovs.is_empty() {
+            framebuffer.enable_aovs(&aovs);
+        }
+        Renderer { settings, framebuffer }
+    }
+
+    pub fn settings(&self) -> &RenderSettings {
+        &self.settings
+    }
+
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    // La escena debe estar ya en el instante que se quiere (Scene::set_time)
+    pub fn render(&mut self, scene: &Scene, camera: &Camera) -> &Framebuffer {
+        render(&mut self.framebuffer, scene, camera, &self.settings);
+        if let Some(denoiser) = &self.settings.denoiser {
+            denoiser.apply(&mut self.framebuffer);
+        }
+        &self.framebuffer
+    }
+}
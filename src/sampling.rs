@@ -0,0 +1,60 @@
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+
+// Generador pseudoaleatorio pequeño (xorshift). Se siembra por píxel, así dos
+// renders de la misma escena dan exactamente la misma imagen.
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    state: u32,
+}
+
+impl Sampler {
+    pub fn new(seed: u32) -> Self {
+        Sampler { state: hash(seed).max(1) }
+    }
+
+    pub fn for_pixel(x: usize, y: usize, frame: u32) -> Self {
+        Sampler::new(hash(x as u32) ^ hash((y as u32).wrapping_mul(0x9E37_79B9)) ^ frame.wrapping_mul(0x85EB_CA6B))
+    }
+
+    // Número uniforme en [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+fn hash(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7FEB_352D);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846C_A68B);
+    x ^= x >> 16;
+    x
+}
+
+// Base ortonormal (tangente, bitangente) alrededor de una normal
+pub fn orthonormal_basis(normal: &Vec3) -> (Vec3, Vec3) {
+    let helper = if normal.x.abs() > 0.9 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    let tangent = normal.cross(&helper).normalize();
+    let bitangent = normal.cross(&tangent);
+    (tangent, bitangent)
+}
+
+// Dirección en el hemisferio de la normal con densidad proporcional al coseno
+pub fn cosine_hemisphere(normal: &Vec3, sampler: &mut Sampler) -> Vec3 {
+    let u1 = sampler.next_f32();
+    let u2 = sampler.next_f32();
+
+    let r = u1.sqrt();
+    let phi = 2.0 * PI * u2;
+    let (tangent, bitangent) = orthonormal_basis(normal);
+
+    (tangent * (r * phi.cos()) + bitangent * (r * phi.sin()) + normal * (1.0 - u1).max(0.0).sqrt()).normalize()
+}
@@ -0,0 +1,101 @@
+use std::path::Path;
+
+use nalgebra_glm::Vec3;
+
+use crate::ambient::AmbientLight;
+use crate::animation::Timeline;
+use crate::camera::Camera;
+use crate::camera_path::CameraPath;
+use crate::color::Color;
+use crate::day_cycle::DayCycle;
+use crate::light::Light;
+use crate::media::Fog;
+use crate::ray_intersect::RayIntersect;
+use crate::scene_file;
+
+// Todo lo que hace falta para renderizar un cuadro. El sol, si lo hay, es la luz
+// que mueve el ciclo de día y la única que hace rayos de luz en la niebla.
+// Fuera del crate se arma con `new` y los `with_*`, así agregar un campo no rompe
+// a nadie.
+#[non_exhaustive]
+pub struct Scene {
+    pub objects: Vec<Box<dyn RayIntersect>>,
+    pub lights: Vec<Light>,
+    pub sun: Option<usize>, // índice en `lights`
+    pub camera: Camera,
+    pub camera_path: Option<CameraPath>,
+    pub timeline: Timeline,
+    pub day_cycle: DayCycle,
+    pub ambient: AmbientLight,
+    pub fog: Fog,
+}
+
+impl Scene {
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        scene_file::load(path)
+    }
+
+    // Sin sol, animaciones, luz ambiental ni niebla; el ciclo de día dura 60 s
+    pub fn new(objects: Vec<Box<dyn RayIntersect>>, lights: Vec<Light>, camera: Camera) -> Self {
+        Scene {
+            objects,
+            lights,
+            sun: None,
+            camera,
+            camera_path: None,
+            timeline: Timeline::new(),
+            day_cycle: DayCycle::new(60.0),
+            ambient: AmbientLight::new(Color::black(), 0.0),
+            fog: Fog::none(),
+        }
+    }
+
+    // Escena sin objetos ni luces, para mostrar algo mientras no se pueda cargar otra
+    pub fn empty() -> Self {
+        let camera = Camera::new(Vec3::new(0.0, 0.0, 5.0), Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+        Scene::new(Vec::new(), Vec::new(), camera)
+    }
+
+    // `index` es la posición del sol en `lights`
+    pub fn with_sun(mut self, index: usize) -> Self {
+        self.sun = Some(index);
+        self
+    }
+
+    pub fn with_camera_path(mut self, camera_path: CameraPath) -> Self {
+        self.camera_path = Some(camera_path);
+        self
+    }
+
+    pub fn with_timeline(mut self, timeline: Timeline) -> Self {
+        self.timeline = timeline;
+        self
+    }
+
+    pub fn with_day_cycle(mut self, day_cycle: DayCycle) -> Self {
+        self.day_cycle = day_cycle;
+        self
+    }
+
+    pub fn with_ambient(mut self, ambient: AmbientLight) -> Self {
+        self.ambient = ambient;
+        self
+    }
+
+    pub fn with_fog(mut self, fog: Fog) -> Self {
+        self.fog = fog;
+        self
+    }
+
+    pub fn sun(&self) -> Option<&Light> {
+        self.sun.and_then(|index| self.lights.get(index))
+    }
+
+    // Lleva las animaciones y el sol al tiempo dado; las demás luces no siguen el día
+    pub fn set_time(&mut self, time: f32) {
+        self.timeline.apply(time, &mut self.objects, &mut self.lights);
+        if let Some(sun) = self.sun.and_then(|index| self.lights.get_mut(index)) {
+            self.day_cycle.apply(sun, time);
+        }
+    }
+}
@@ -0,0 +1,525 @@
+use std::path::{Path, PathBuf};
+
+use nalgebra_glm::{Quat, Vec3};
+use serde::Deserialize;
+
+use crate::ambient::AmbientLight;
+use crate::animation::{Animatable, Interpolation, LightAnimation, MaterialAnimation, Timeline, Track};
+use crate::assets::Assets;
+use crate::camera::Camera;
+use crate::camera_path::CameraPath;
+use crate::color::Color;
+use crate::cube::Cube;
+use crate::day_cycle::DayCycle;
+use crate::gltf_import;
+use crate::grid::Grid;
+use crate::group::Group;
+use crate::light::Light;
+use crate::material::Material;
+use crate::media::{Fog, Medium};
+use crate::ray_intersect::{RayIntersect, bounds_of};
+use crate::scene::Scene;
+use crate::vox;
+use crate::water::{GerstnerWave, Water};
+
+// Escena descrita en TOML. Las rutas (texturas, recorrido de cámara) son relativas
+// a la carpeta del archivo. El FOV va en grados, los colores en RGB de 0 a 255.
+// Un .gltf o .glb también se puede abrir directo: se le agregan un sol y luz ambiental.
+pub fn load(path: &Path) -> Result<Scene, String> {
+    load_tracked(path, &mut Vec::new())
+}
+
+// Como `load`, pero anota en `sources` cada archivo que intenta leer, incluso si
+// la carga falla a medio camino, para poder vigilarlos
+pub fn load_tracked(path: &Path, sources: &mut Vec<PathBuf>) -> Result<Scene, String> {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    if let Some(name) = gltf_file_name(path) {
+        return SceneDesc::for_gltf(name).build(dir, sources);
+    }
+
+    sources.push(path.to_path_buf());
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let desc: SceneDesc = toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    desc.build(dir, sources).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn gltf_file_name(path: &Path) -> Option<String> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    if extension != "gltf" && extension != "glb" {
+        return None;
+    }
+    Some(path.file_name()?.to_string_lossy().into_owned())
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDesc {
+    // Sin [camera] se usa la primera cámara de un glTF, o una que encuadre todo
+    camera: Option<CameraDesc>,
+    camera_path: Option<String>,
+    #[serde(default = "default_day_length")]
+    day_length: f32,
+    ambient: Option<AmbientDesc>,
+    fog: Option<FogDesc>,
+    #[serde(default)]
+    lights: Vec<LightDesc>,
+    #[serde(default)]
+    materials: Vec<MaterialDesc>,
+    #[serde(default)]
+    objects: Vec<ObjectDesc>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraDesc {
+    eye: [f32; 3],
+    center: [f32; 3],
+    #[serde(default = "default_up")]
+    up: [f32; 3],
+    #[serde(default = "default_fov")]
+    fov: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AmbientDesc {
+    color: [u8; 3],
+    intensity: f32,
+    #[serde(default)]
+    occlusion_samples: u32,
+    #[serde(default)]
+    occlusion_radius: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FogDesc {
+    color: [u8; 3],
+    density: f32,
+    #[serde(default)]
+    height_density: f32,
+    #[serde(default = "default_one")]
+    height_falloff: f32,
+    #[serde(default)]
+    base_height: f32,
+    #[serde(default = "default_sky_distance")]
+    sky_distance: f32,
+    #[serde(default)]
+    scattering: f32,
+    #[serde(default)]
+    shaft_steps: u32,
+    #[serde(default)]
+    anisotropy: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LightDesc {
+    position: [f32; 3],
+    color: [u8; 3],
+    intensity: f32,
+    #[serde(default)]
+    attenuation: f32,
+    // El sol sigue el ciclo de día; puede haber uno o ninguno
+    #[serde(default)]
+    sun: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaterialDesc {
+    name: String,
+    color: [u8; 3],
+    specular: f32,
+    albedo: [f32; 4],
+    #[serde(default = "default_one")]
+    refractive_index: f32,
+    texture: Option<String>,
+    emission: Option<EmissionDesc>,
+    medium: Option<MediumDesc>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EmissionDesc {
+    color: [u8; 3],
+    strength: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MediumDesc {
+    color: [u8; 3],
+    density: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ObjectDesc {
+    Cube {
+        min: [f32; 3],
+        max: [f32; 3],
+        material: String,
+        // Luces puntuales por cara si el material emite luz
+        #[serde(default)]
+        emissive_lights: usize,
+        // Factor sobre la emisión del material y la intensidad de sus luces
+        flicker: Option<TrackDesc<f32>>,
+    },
+    Grid {
+        material: String,
+        size: [usize; 2],
+        cube_size: f32,
+        #[serde(default)]
+        offset: [f32; 3],
+    },
+    Water {
+        material: String,
+        size: [usize; 2],
+        cube_size: f32,
+        #[serde(default)]
+        offset: [f32; 3],
+        #[serde(default)]
+        waves: Vec<WaveDesc>,
+        #[serde(default)]
+        animated_heights: bool,
+    },
+    Group {
+        #[serde(default)]
+        offset: [f32; 3],
+        offset_track: Option<TrackDesc<[f32; 3]>>,
+        rotation_track: Option<TrackDesc<[f32; 3]>>, // grados alrededor de X, Y y Z, en ese orden
+        scale_track: Option<TrackDesc<[f32; 3]>>,
+        children: Vec<ObjectDesc>,
+    },
+    // Escena glTF 2.0 (.gltf o .glb) con sus materiales, luces y cámaras
+    Gltf {
+        path: String,
+        #[serde(default)]
+        offset: [f32; 3],
+    },
+    // Modelo de MagicaVoxel; los materiales salen de su paleta
+    Vox {
+        path: String,
+        #[serde(default = "default_one")]
+        voxel_size: f32,
+        #[serde(default)]
+        offset: [f32; 3],
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WaveDesc {
+    direction: [f32; 2],
+    wavelength: f32,
+    amplitude: f32,
+    steepness: f32,
+    speed: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TrackDesc<T> {
+    #[serde(default)]
+    looped: bool,
+    keys: Vec<KeyDesc<T>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeyDesc<T> {
+    time: f32,
+    value: T,
+    #[serde(default = "default_interpolation")]
+    interpolation: Interpolation,
+}
+
+fn default_day_length() -> f32 {
+    60.0
+}
+
+fn default_up() -> [f32; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+fn default_fov() -> f32 {
+    60.0
+}
+
+fn default_sky_distance() -> f32 {
+    12.0
+}
+
+fn default_one() -> f32 {
+    1.0
+}
+
+fn default_interpolation() -> Interpolation {
+    Interpolation::Linear
+}
+
+fn color([r, g, b]: [u8; 3]) -> Color {
+    Color::new(r, g, b)
+}
+
+// Ángulos en grados, aplicados alrededor de X, luego Y y luego Z
+fn rotation(degrees: [f32; 3]) -> Quat {
+    let [x, y, z] = [0, 1, 2].map(|axis| {
+        nalgebra_glm::quat_angle_axis(degrees[axis].to_radians(), &Vec3::from_fn(|i, _| if i == axis { 1.0 } else { 0.0 }))
+    });
+    z * y * x
+}
+
+impl<T: Clone> TrackDesc<T> {
+    fn build<U: Animatable>(&self, value: impl Fn(&T) -> U) -> Track<U> {
+        let track = self
+            .keys
+            .iter()
+            .fold(Track::new(), |track, key| track.key(key.time, value(&key.value), key.interpolation));
+        if self.looped { track.looped() } else { track }
+    }
+}
+
+// Lo que se va juntando al recorrer los objetos: las luces de los cubos
+// emisivos y de los glTF, las animaciones de esas luces (con índices relativos
+// a ellas mismas) y las cámaras de los glTF
+#[derive(Default)]
+struct Emitters {
+    lights: Vec<Light>,
+    animations: Vec<(usize, LightAnimation)>,
+    cameras: Vec<Camera>,
+}
+
+impl SceneDesc {
+    // Escena para abrir un glTF directo: el sol de siempre arriba y algo de luz ambiental
+    fn for_gltf(path: String) -> Self {
+        SceneDesc {
+            camera: None,
+            camera_path: None,
+            day_length: default_day_length(),
+            ambient: Some(AmbientDesc { color: [102, 153, 255], intensity: 0.25, occlusion_samples: 0, occlusion_radius: 0.0 }),
+            fog: None,
+            lights: vec![LightDesc {
+                position: [0.0, 10.0, 0.0],
+                color: [255, 255, 255],
+                intensity: 1.0,
+                attenuation: 0.0,
+                sun: true,
+            }],
+            materials: Vec::new(),
+            objects: vec![ObjectDesc::Gltf { path, offset: [0.0; 3] }],
+        }
+    }
+
+    fn build(self, dir: &Path, sources: &mut Vec<PathBuf>) -> Result<Scene, String> {
+        let mut assets = Assets::new();
+        for desc in &self.materials {
+            let texture = match &desc.texture {
+                Some(texture) => {
+                    let path = dir.join(texture);
+                    sources.push(path.clone());
+                    Some(assets.texture(&path.to_string_lossy())?)
+                }
+                None => None,
+            };
+            let mut material = Material::new(color(desc.color), desc.specular, desc.albedo, desc.refractive_index, texture);
+            if let Some(emission) = &desc.emission {
+                material = material.with_emission(color(emission.color), emission.strength);
+            }
+            if let Some(medium) = &desc.medium {
+                material = material.with_medium(Medium::new(color(medium.color), medium.density));
+            }
+            assets.add_material(&desc.name, material)?;
+        }
+
+        let mut emitters = Emitters::default();
+        let objects = self
+            .objects
+            .iter()
+            .map(|object| object.build(&mut assets, dir, sources, Vec3::zeros(), &mut emitters))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let mut lights: Vec<Light> = self
+            .lights
+            .iter()
+            .map(|light| {
+                Light::new(Vec3::from(light.position), color(light.color), light.intensity)
+                    .with_attenuation(light.attenuation)
+            })
+            .collect();
+        let mut suns = self.lights.iter().enumerate().filter(|(_, light)| light.sun).map(|(index, _)| index);
+        let sun = suns.next();
+        if suns.next().is_some() {
+            return Err("solo una luz puede ser el sol".to_string());
+        }
+        let mut timeline = Timeline::new();
+        for (index, animation) in emitters.animations {
+            timeline = timeline.with_light(lights.len() + index, animation);
+        }
+        lights.extend(emitters.lights);
+
+        let camera = match &self.camera {
+            Some(camera) => Camera::new(Vec3::from(camera.eye), Vec3::from(camera.center), Vec3::from(camera.up))
+                .with_fov(camera.fov.to_radians()),
+            None => match emitters.cameras.first() {
+                Some(camera) => camera.clone(),
+                None => framing_camera(&objects),
+            },
+        };
+
+        let camera_path = match &self.camera_path {
+            Some(path) => {
+                let path = dir.join(path);
+                sources.push(path.clone());
+                Some(CameraPath::from_file(&path.to_string_lossy())?)
+            }
+            None => None,
+        };
+
+        let ambient = match &self.ambient {
+            Some(ambient) => AmbientLight::new(color(ambient.color), ambient.intensity)
+                .with_occlusion(ambient.occlusion_samples, ambient.occlusion_radius),
+            None => AmbientLight::new(Color::black(), 0.0),
+        };
+
+        let fog = match &self.fog {
+            Some(fog) => Fog::new(color(fog.color), fog.density)
+                .with_height_fog(fog.height_density, fog.height_falloff, fog.base_height)
+                .with_sky_distance(fog.sky_distance)
+                .with_light_shafts(fog.scattering, fog.shaft_steps, fog.anisotropy),
+            None => Fog::none(),
+        };
+
+        Ok(Scene {
+            objects,
+            lights,
+            sun,
+            camera,
+            camera_path,
+            timeline,
+            day_cycle: DayCycle::new(self.day_length),
+            ambient,
+            fog,
+        })
+    }
+}
+
+impl ObjectDesc {
+    // `offset` es la posición del grupo que lo contiene, para ubicar las luces emisivas
+    fn build(
+        &self,
+        assets: &mut Assets,
+        dir: &Path,
+        sources: &mut Vec<PathBuf>,
+        offset: Vec3,
+        emitters: &mut Emitters,
+    ) -> Result<Box<dyn RayIntersect>, String> {
+        let material = |name: &str| assets.material(name).ok_or_else(|| format!("material desconocido: {}", name));
+
+        Ok(match self {
+            ObjectDesc::Cube { min, max, material: name, emissive_lights, flicker } => {
+                let mut cube = Cube::new(Vec3::from(*min), Vec3::from(*max), material(name)?);
+                let lights = cube.emissive_lights(*emissive_lights);
+
+                if let Some(flicker) = flicker {
+                    let strength = cube.material.emission_strength;
+                    cube = cube.with_material_animation(MaterialAnimation {
+                        emission_strength: Some(flicker.build(|factor| factor * strength)),
+                        ..Default::default()
+                    });
+                    for (i, light) in lights.iter().enumerate() {
+                        let intensity = light.intensity;
+                        emitters.animations.push((emitters.lights.len() + i, LightAnimation {
+                            intensity: Some(flicker.build(|factor| factor * intensity)),
+                            ..Default::default()
+                        }));
+                    }
+                }
+                emitters.lights.extend(lights.into_iter().map(|mut light| {
+                    light.position += offset;
+                    light
+                }));
+                Box::new(cube)
+            }
+            ObjectDesc::Grid { material: name, size, cube_size, offset: [x, y, z] } => {
+                Box::new(Grid::create_cuadricula(size[0], size[1], *cube_size, material(name)?, *x, *y, *z))
+            }
+            ObjectDesc::Water { material: name, size, cube_size, offset: [x, y, z], waves, animated_heights } => {
+                let waves = waves
+                    .iter()
+                    .map(|w| GerstnerWave::new((w.direction[0], w.direction[1]), w.wavelength, w.amplitude, w.steepness, w.speed))
+                    .collect();
+                Box::new(
+                    Water::create_cuadricula(size[0], size[1], *cube_size, material(name)?, *x, *y, *z)
+                        .with_waves(waves)
+                        .with_animated_heights(*animated_heights),
+                )
+            }
+            ObjectDesc::Group { offset: group_offset, offset_track, rotation_track, scale_track, children } => {
+                let group_offset = Vec3::from(*group_offset);
+                let elements = children
+                    .iter()
+                    .map(|child| child.build(assets, dir, sources, offset + group_offset, emitters))
+                    .collect::<Result<Vec<_>, String>>()?;
+                let mut group = Group::new(elements, group_offset);
+                if let Some(track) = offset_track {
+                    group = group.with_offset_track(track.build(|value| Vec3::from(*value)));
+                }
+                if let Some(track) = rotation_track {
+                    group = group.with_rotation_track(track.build(|value| rotation(*value)));
+                }
+                if let Some(track) = scale_track {
+                    group = group.with_scale_track(track.build(|value| Vec3::from(*value)));
+                }
+                Box::new(group)
+            }
+            ObjectDesc::Gltf { path, offset: gltf_offset } => {
+                let path = dir.join(path);
+                sources.push(path.clone());
+                let scene = gltf_import::load(&path, assets)?;
+                sources.extend(scene.files);
+
+                let gltf_offset = Vec3::from(*gltf_offset);
+                let world_offset = offset + gltf_offset;
+                emitters.lights.extend(scene.lights.into_iter().map(|mut light| {
+                    light.position += world_offset;
+                    light
+                }));
+                emitters.cameras.extend(scene.cameras.into_iter().map(|mut camera| {
+                    camera.eye += world_offset;
+                    camera.center += world_offset;
+                    camera
+                }));
+                let mut group = scene.root;
+                group.set_offset(gltf_offset);
+                Box::new(group)
+            }
+            ObjectDesc::Vox { path, voxel_size, offset } => {
+                if *voxel_size <= 0.0 {
+                    return Err(format!("{}: voxel_size tiene que ser mayor que 0", path));
+                }
+                let path = dir.join(path);
+                sources.push(path.clone());
+                let mut group = vox::load(&path, assets, *voxel_size)?;
+                group.set_offset(Vec3::from(*offset));
+                Box::new(group)
+            }
+        })
+    }
+}
+
+// Cámara que mira el centro de todos los objetos desde arriba y adelante, a una
+// distancia en la que entran enteros
+fn framing_camera(objects: &[Box<dyn RayIntersect>]) -> Camera {
+    let up = Vec3::new(0.0, 1.0, 0.0);
+    match bounds_of(objects) {
+        Some(bounds) => {
+            let center = (bounds.min + bounds.max) * 0.5;
+            let radius = ((bounds.max - bounds.min).norm() * 0.5).max(1e-3);
+            let eye = center + Vec3::new(0.0, 0.5, 1.0).normalize() * radius * 2.5;
+            Camera::new(eye, center, up)
+        }
+        None => Camera::new(Vec3::new(0.0, 0.0, 5.0), Vec3::zeros(), up),
+    }
+}
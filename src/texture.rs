@@ -6,9 +6,9 @@ pub struct Texture {
 }
 
 impl Texture {
-    pub fn from_file(path: &str) -> Self {
-        let img = image::open(path).expect("Failed to open texture image");
-        Self { image: img.to_rgba8() }
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let img = image::open(path).map_err(|e| format!("{}: {}", path, e))?;
+        Ok(Self { image: img.to_rgba8() })
     }
 
     pub fn get_color_at(&self, uv: (f32, f32)) -> [u8; 4] {
@@ -0,0 +1,554 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use nalgebra_glm::{Mat3, Vec3};
+
+use crate::assets::Assets;
+use crate::color::Color;
+use crate::group::Group;
+use crate::material::Material;
+use crate::ray_intersect::RayIntersect;
+use crate::voxel::{VoxelGrid, VoxelModel};
+
+// MagicaVoxel usa Z hacia arriba; acá arriba es Y. (x, y, z) pasa a (x, z, -y).
+const Z_UP_TO_Y_UP: [f32; 9] = [
+    1.0, 0.0, 0.0,
+    0.0, 0.0, 1.0,
+    0.0, -1.0, 0.0,
+];
+
+// Lo más grande que permite MagicaVoxel por eje
+const MAX_MODEL_SIZE: usize = 256;
+
+// Propiedades de un material de MagicaVoxel (chunk MATL) que se traducen a Material
+#[derive(Debug, Clone, Default)]
+struct VoxMaterial {
+    kind: String, // _diffuse, _metal, _glass, _emit; el resto se trata como difuso
+    properties: HashMap<String, String>,
+}
+
+impl VoxMaterial {
+    fn get(&self, key: &str, default: f32) -> f32 {
+        self.properties.get(key).and_then(|value| value.parse().ok()).unwrap_or(default)
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Transform { child: i32, rotation: Mat3, translation: Vec3, hidden: bool },
+    Group { children: Vec<i32> },
+    Shape { models: Vec<usize> },
+}
+
+// Contenido de un archivo .vox, tal como viene
+struct VoxFile {
+    models: Vec<Arc<VoxelModel>>,
+    palette: [Color; 256], // índice 0 sin usar: es el voxel vacío
+    materials: HashMap<u8, VoxMaterial>,
+    nodes: HashMap<i32, Node>,
+}
+
+// Carga un .vox de MagicaVoxel como un grupo con una grilla por cada modelo que
+// aparece en la escena del archivo, ya con su rotación y traslación. Cada voxel
+// mide `voxel_size`. Los materiales de la paleta se registran en `assets` como
+// "<ruta>#<índice>".
+pub fn load(path: &Path, assets: &mut Assets, voxel_size: f32) -> Result<Group, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let file = parse(&bytes).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let materials = Arc::new(file.materials(assets, path));
+
+    let mut instances = Vec::new();
+    if file.nodes.contains_key(&0) {
+        file.collect_instances(0, Mat3::identity(), Vec3::zeros(), &mut instances, 0)?;
+    } else {
+        // Archivos sin grafo de escena: todos los modelos en el origen
+        instances.extend((0..file.models.len()).map(|model| (model, Mat3::identity(), Vec3::zeros())));
+    }
+
+    let world = Mat3::from_row_slice(&Z_UP_TO_Y_UP) * voxel_size;
+    let elements = instances
+        .into_iter()
+        .map(|(index, rotation, translation)| {
+            let model = file.models[index].clone();
+            // MagicaVoxel rota cada modelo alrededor de su centro, redondeado hacia abajo
+            let [x, y, z] = model.size;
+            let pivot = Vec3::new((x / 2) as f32, (y / 2) as f32, (z / 2) as f32);
+            let grid = VoxelGrid::new(model, materials.clone(), world * rotation, world * (translation - rotation * pivot))
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+            Ok(Box::new(grid) as Box<dyn RayIntersect>)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(Group::new(elements, Vec3::zeros()))
+}
+
+impl VoxFile {
+    fn materials(&self, assets: &mut Assets, path: &Path) -> Vec<Arc<Material>> {
+        let mut used = [false; 256];
+        for model in &self.models {
+            for &index in model.cells() {
+                used[index as usize] = true;
+            }
+        }
+        let empty = Arc::new(Material::black());
+
+        (0..256)
+            .map(|index| {
+                if index == 0 || !used[index] {
+                    return empty.clone();
+                }
+                let vox = self.materials.get(&(index as u8)).cloned().unwrap_or_default();
+                assets.imported_material(&format!("{}#{}", path.display(), index), || {
+                    translate_material(self.palette[index], &vox)
+                })
+            })
+            .collect()
+    }
+
+    // Recorre el grafo desde `id` acumulando las transformaciones; deja
+    // (modelo, rotación, traslación) por cada modelo alcanzado
+    fn collect_instances(
+        &self,
+        id: i32,
+        rotation: Mat3,
+        translation: Vec3,
+        instances: &mut Vec<(usize, Mat3, Vec3)>,
+        depth: usize,
+    ) -> Result<(), String> {
+        // Un grafo con ciclos no es válido, pero no debe colgar la carga
+        if depth > self.nodes.len() {
+            return Err("el grafo de escena tiene ciclos".to_string());
+        }
+        match self.nodes.get(&id) {
+            Some(Node::Transform { child, rotation: local_rotation, translation: local_translation, hidden }) => {
+                if !hidden {
+                    let rotation_child = rotation * local_rotation;
+                    let translation_child = rotation * local_translation + translation;
+                    self.collect_instances(*child, rotation_child, translation_child, instances, depth + 1)?;
+                }
+            }
+            Some(Node::Group { children }) => {
+                for child in children {
+                    self.collect_instances(*child, rotation, translation, instances, depth + 1)?;
+                }
+            }
+            Some(Node::Shape { models }) => {
+                for &model in models {
+                    if model >= self.models.len() {
+                        return Err(format!("el nodo {} usa el modelo {}, que no existe", id, model));
+                    }
+                    instances.push((model, rotation, translation));
+                }
+            }
+            None => return Err(format!("nodo {} inexistente", id)),
+        }
+        Ok(())
+    }
+}
+
+// MATL a Material. Las propiedades van de 0 a 1; _ior puede venir como el índice
+// menos uno, como lo muestra MagicaVoxel.
+fn translate_material(color: Color, vox: &VoxMaterial) -> Material {
+    let rough = vox.get("_rough", 0.5).clamp(0.0, 1.0);
+    // Más rugoso, brillo más ancho
+    let specular = 10.0 + 990.0 * (1.0 - rough).powi(2);
+
+    match vox.kind.as_str() {
+        "_metal" => {
+            let metal = vox.get("_metal", 1.0).clamp(0.0, 1.0);
+            let reflect = 0.8 * metal * (1.0 - rough);
+            Material::new(color, specular, [1.0 - reflect, 0.3 + 0.5 * metal, reflect, 0.0], 1.0, None)
+        }
+        "_glass" => {
+            let trans = vox.get("_trans", 0.5).clamp(0.0, 1.0);
+            let ior = vox.get("_ior", 0.3);
+            let ior = if ior < 1.0 { ior + 1.0 } else { ior };
+            Material::new(color, specular.max(500.0), [0.9 * (1.0 - trans) + 0.1, 0.5, 0.1, 0.8 * trans], ior, None)
+        }
+        "_emit" => {
+            // _flux va de 0 a 4 y multiplica la potencia
+            let strength = vox.get("_emit", 0.0) * (1.0 + vox.get("_flux", 0.0));
+            Material::new(color, 10.0, [0.9, 0.1, 0.0, 0.0], 1.0, None).with_emission(color, strength)
+        }
+        _ => Material::new(color, specular, [0.9, 0.2, 0.0, 0.0], 1.0, None),
+    }
+}
+
+// Paleta por defecto de MagicaVoxel, para archivos sin chunk RGBA: un cubo de 6
+// niveles por canal sin el negro, y rampas de rojo, verde, azul y gris
+fn default_palette() -> [Color; 256] {
+    const LEVELS: [u8; 6] = [0xff, 0xcc, 0x99, 0x66, 0x33, 0x00];
+    const RAMP: [u8; 10] = [0xee, 0xdd, 0xbb, 0xaa, 0x88, 0x77, 0x55, 0x44, 0x22, 0x11];
+
+    let mut palette = [Color::black(); 256];
+    let mut index = 1;
+    for r in LEVELS {
+        for g in LEVELS {
+            for b in LEVELS {
+                if (r, g, b) != (0, 0, 0) {
+                    palette[index] = Color::new(r, g, b);
+                    index += 1;
+                }
+            }
+        }
+    }
+    for channel in 0..4 {
+        for level in RAMP {
+            palette[index] = match channel {
+                0 => Color::new(level, 0, 0),
+                1 => Color::new(0, level, 0),
+                2 => Color::new(0, 0, level),
+                _ => Color::new(level, level, level),
+            };
+            index += 1;
+        }
+    }
+    palette
+}
+
+// Lector de los datos en little-endian de un chunk
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        if count > self.bytes.len() {
+            return Err("el archivo termina antes de tiempo".to_string());
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        let bytes = self.take(4)?;
+        Ok(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    // Cantidades y tamaños: enteros no negativos
+    fn count(&mut self) -> Result<usize, String> {
+        let value = self.i32()?;
+        usize::try_from(value).map_err(|_| format!("cantidad inválida: {}", value))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let length = self.count()?;
+        Ok(String::from_utf8_lossy(self.take(length)?).into_owned())
+    }
+
+    fn dict(&mut self) -> Result<HashMap<String, String>, String> {
+        let entries = self.count()?;
+        (0..entries).map(|_| Ok((self.string()?, self.string()?))).collect()
+    }
+}
+
+fn parse(bytes: &[u8]) -> Result<VoxFile, String> {
+    let mut reader = Reader { bytes };
+    if reader.take(4)? != b"VOX " {
+        return Err("no es un archivo .vox".to_string());
+    }
+    reader.i32()?; // versión
+
+    // MAIN no tiene contenido propio; los demás chunks son sus hijos
+    if reader.take(4)? != b"MAIN" {
+        return Err("falta el chunk MAIN".to_string());
+    }
+    let content = reader.count()?;
+    reader.count()?;
+    reader.take(content)?;
+
+    let mut file = VoxFile {
+        models: Vec::new(),
+        palette: default_palette(),
+        materials: HashMap::new(),
+        nodes: HashMap::new(),
+    };
+    let mut size = None;
+
+    while !reader.bytes.is_empty() {
+        let id = reader.take(4)?;
+        let content = reader.count()?;
+        let children = reader.count()?;
+        let mut chunk = Reader { bytes: reader.take(content)? };
+        reader.take(children)?;
+
+        match id {
+            b"SIZE" => {
+                let dimensions = [chunk.count()?, chunk.count()?, chunk.count()?];
+                if dimensions.iter().any(|&d| d == 0 || d > MAX_MODEL_SIZE) {
+                    return Err(format!("tamaño de modelo inválido: {:?}", dimensions));
+                }
+                size = Some(dimensions);
+            }
+            b"XYZI" => {
+                let size = size.take().ok_or("chunk XYZI sin SIZE")?;
+                let mut model = VoxelModel::new(size);
+                for _ in 0..chunk.count()? {
+                    let [x, y, z, index] = [chunk.u8()?, chunk.u8()?, chunk.u8()?, chunk.u8()?];
+                    model.set([x as usize, y as usize, z as usize], index);
+                }
+                file.models.push(Arc::new(model));
+            }
+            // El color i del chunk es el del índice de paleta i + 1
+            b"RGBA" => {
+                for index in 1..256 {
+                    let [r, g, b, _] = [chunk.u8()?, chunk.u8()?, chunk.u8()?, chunk.u8()?];
+                    file.palette[index] = Color::new(r, g, b);
+                }
+            }
+            b"MATL" => {
+                let index = chunk.i32()?;
+                let mut properties = chunk.dict()?;
+                let kind = properties.remove("_type").unwrap_or_default();
+                if let Ok(index) = u8::try_from(index) {
+                    file.materials.insert(index, VoxMaterial { kind, properties });
+                }
+            }
+            b"nTRN" => {
+                let node = chunk.i32()?;
+                let attributes = chunk.dict()?;
+                let child = chunk.i32()?;
+                chunk.i32()?; // reservado
+                chunk.i32()?; // capa
+                // Solo se usa el primer cuadro de la animación
+                let frame = if chunk.count()? > 0 { chunk.dict()? } else { HashMap::new() };
+                let rotation = frame.get("_r").map_or(Ok(Mat3::identity()), |r| parse_rotation(r))?;
+                let translation = frame.get("_t").map_or(Ok(Vec3::zeros()), |t| parse_translation(t))?;
+                let hidden = attributes.get("_hidden").is_some_and(|value| value == "1");
+                file.nodes.insert(node, Node::Transform { child, rotation, translation, hidden });
+            }
+            b"nGRP" => {
+                let node = chunk.i32()?;
+                chunk.dict()?;
+                let children = (0..chunk.count()?).map(|_| chunk.i32()).collect::<Result<_, _>>()?;
+                file.nodes.insert(node, Node::Group { children });
+            }
+            b"nSHP" => {
+                let node = chunk.i32()?;
+                chunk.dict()?;
+                let mut models = Vec::new();
+                for _ in 0..chunk.count()? {
+                    models.push(chunk.count()?);
+                    chunk.dict()?;
+                }
+                file.nodes.insert(node, Node::Shape { models });
+            }
+            // PACK, LAYR, rOBJ, rCAM, NOTE, IMAP...: no hacen falta para la geometría
+            _ => {}
+        }
+    }
+
+    Ok(file)
+}
+
+// _r guarda la matriz de rotación en un byte: en los bits 0-1 y 2-3 la columna del
+// 1 de las filas 0 y 1 (la fila 2 usa la que queda), y en los bits 4, 5 y 6 el
+// signo de cada fila
+fn parse_rotation(value: &str) -> Result<Mat3, String> {
+    let bits: u8 = value.parse().map_err(|_| format!("rotación inválida: {}", value))?;
+    let first = (bits & 3) as usize;
+    let second = ((bits >> 2) & 3) as usize;
+    if first > 2 || second > 2 || first == second {
+        return Err(format!("rotación inválida: {}", value));
+    }
+    let third = 3 - first - second;
+
+    let mut rotation = Mat3::zeros();
+    for (row, column) in [first, second, third].into_iter().enumerate() {
+        rotation[(row, column)] = if bits & (1 << (4 + row)) != 0 { -1.0 } else { 1.0 };
+    }
+    Ok(rotation)
+}
+
+fn parse_translation(value: &str) -> Result<Vec3, String> {
+    let parts = value
+        .split_whitespace()
+        .map(|part| part.parse::<f32>().map_err(|_| format!("traslación inválida: {}", value)))
+        .collect::<Result<Vec<_>, _>>()?;
+    match parts[..] {
+        [x, y, z] => Ok(Vec3::new(x, y, z)),
+        _ => Err(format!("traslación inválida: {}", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ray::Ray;
+
+    const RED: u32 = 0xff0000;
+    const GREEN: u32 = 0x00ff00;
+
+    fn int(value: i32) -> Vec<u8> {
+        value.to_le_bytes().to_vec()
+    }
+
+    fn string(value: &str) -> Vec<u8> {
+        [int(value.len() as i32), value.as_bytes().to_vec()].concat()
+    }
+
+    fn dict(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut bytes = int(entries.len() as i32);
+        for (key, value) in entries {
+            bytes.extend(string(key));
+            bytes.extend(string(value));
+        }
+        bytes
+    }
+
+    fn chunk(id: &[u8; 4], content: &[u8]) -> Vec<u8> {
+        [id.to_vec(), int(content.len() as i32), int(0), content.to_vec()].concat()
+    }
+
+    fn vox_file(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let children = chunks.concat();
+        [b"VOX ".to_vec(), int(150), b"MAIN".to_vec(), int(0), int(children.len() as i32), children].concat()
+    }
+
+    // Un modelo de 2x1x1 (rojo y verde metálico) girado 90° alrededor de Z y
+    // trasladado a x = 10, dentro de un grupo
+    fn fixture() -> Vec<u8> {
+        let mut palette = Vec::new();
+        for index in 1..256 {
+            palette.extend(match index {
+                1 => [255, 0, 0, 255],
+                2 => [0, 255, 0, 255],
+                _ => [128, 128, 128, 255],
+            });
+        }
+        vox_file(&[
+            chunk(b"SIZE", &[int(2), int(1), int(1)].concat()),
+            chunk(b"XYZI", &[int(2), vec![0, 0, 0, 1], vec![1, 0, 0, 2]].concat()),
+            chunk(b"RGBA", &palette),
+            chunk(b"MATL", &[int(2), dict(&[("_type", "_metal"), ("_metal", "1"), ("_rough", "0")])].concat()),
+            chunk(b"nTRN", &[int(0), dict(&[]), int(1), int(-1), int(-1), int(1), dict(&[])].concat()),
+            chunk(b"nGRP", &[int(1), dict(&[]), int(1), int(2)].concat()),
+            // _r = 17: la fila 0 toma -y y la fila 1 toma x
+            chunk(b"nTRN", &[int(2), dict(&[]), int(3), int(-1), int(-1), int(1), dict(&[("_r", "17"), ("_t", "10 0 0")])].concat()),
+            chunk(b"nSHP", &[int(3), dict(&[]), int(1), int(0), dict(&[])].concat()),
+        ])
+    }
+
+    // Escribe los bytes en un archivo temporal propio de cada prueba y lo carga
+    fn load_bytes(name: &str, bytes: &[u8], assets: &mut Assets) -> Result<Group, String> {
+        let path = std::env::temp_dir().join(format!("proyecto2raytracing_{}_{}.vox", std::process::id(), name));
+        std::fs::write(&path, bytes).unwrap();
+        let result = load(&path, assets, 1.0);
+        let _ = std::fs::remove_file(&path);
+        result
+    }
+
+    #[test]
+    fn lee_tamanos_y_paleta() {
+        let file = parse(&fixture()).unwrap();
+        assert_eq!(file.models.len(), 1);
+        assert_eq!(file.models[0].size, [2, 1, 1]);
+        assert_eq!(file.models[0].get([0, 0, 0]), 1);
+        assert_eq!(file.models[0].get([1, 0, 0]), 2);
+        // El color i del chunk RGBA es el índice i + 1
+        assert_eq!(file.palette[1].to_hex(), RED);
+        assert_eq!(file.palette[2].to_hex(), GREEN);
+        assert_eq!(file.materials[&2].kind, "_metal");
+
+        let rotation = parse_rotation("17").unwrap();
+        assert_eq!(rotation * Vec3::new(1.0, 2.0, 3.0), Vec3::new(-2.0, 1.0, 3.0));
+        assert!(parse_rotation("15").is_err());
+    }
+
+    #[test]
+    fn sin_rgba_usa_la_paleta_por_defecto() {
+        let file = parse(&vox_file(&[
+            chunk(b"SIZE", &[int(1), int(1), int(1)].concat()),
+            chunk(b"XYZI", &[int(1), vec![0, 0, 0, 2]].concat()),
+        ]))
+        .unwrap();
+        assert_eq!(file.palette[1].to_hex(), 0xffffff);
+        assert_eq!(file.palette[2].to_hex(), 0xffffcc);
+        assert_eq!(file.palette[255].to_hex(), 0x111111);
+    }
+
+    #[test]
+    fn carga_la_instancia_girada() {
+        let mut assets = Assets::new();
+        let group = load_bytes("girada", &fixture(), &mut assets).unwrap();
+
+        let bounds = group.bounds().unwrap();
+        assert!((bounds.min - Vec3::new(9.0, 0.0, -1.0)).norm() < 1e-4, "{:?}", bounds);
+        assert!((bounds.max - Vec3::new(10.0, 1.0, 1.0)).norm() < 1e-4, "{:?}", bounds);
+
+        // Materiales registrados por índice de paleta, solo los que se usan
+        let name = std::env::temp_dir().join(format!("proyecto2raytracing_{}_girada.vox", std::process::id()));
+        let name = name.display();
+        assert_eq!(assets.material(&format!("{}#1", name)).unwrap().diffuse.to_hex(), RED);
+        assert!(assets.material(&format!("{}#2", name)).unwrap().albedo[2] > 0.0);
+        assert!(assets.material(&format!("{}#3", name)).is_none());
+
+        // Desde arriba, el giro deja el voxel rojo en z > 0 y el verde en z < 0
+        let down = |z: f32| Ray::new(Vec3::new(9.5, 5.0, z), Vec3::new(0.0, -1.0, 0.0));
+        let hit = group.ray_intersect(&down(0.5)).unwrap();
+        assert!((hit.distance - 4.0).abs() < 1e-4);
+        assert!((hit.normal - Vec3::new(0.0, 1.0, 0.0)).norm() < 1e-4);
+        assert_eq!(hit.material.diffuse.to_hex(), RED);
+        assert_eq!(group.ray_intersect(&down(-0.5)).unwrap().material.diffuse.to_hex(), GREEN);
+        assert!(group.ray_intersect(&down(1.5)).is_none());
+    }
+
+    #[test]
+    fn archivos_truncados_o_rotos_dan_error() {
+        assert!(parse(b"").is_err());
+        assert!(parse(b"RIFF\x96\0\0\0").is_err());
+        assert!(parse(b"VOX \x96\0\0\0MAIN").is_err());
+
+        let bytes = fixture();
+        // Cortado en medio de un chunk
+        for length in [9, 22, 30, bytes.len() - 1] {
+            assert!(parse(&bytes[..length]).is_err(), "{} bytes", length);
+        }
+        // Ningún corte ni byte cambiado puede hacer entrar en pánico a la carga
+        let mut assets = Assets::new();
+        for length in 0..bytes.len() {
+            let _ = load_bytes("cortado", &bytes[..length], &mut assets);
+        }
+        for position in 0..bytes.len() {
+            for value in [0x00, 0x7f, 0xff] {
+                let mut broken = bytes.clone();
+                broken[position] = value;
+                let _ = load_bytes("roto", &broken, &mut assets);
+            }
+        }
+
+        // XYZI sin SIZE, tamaño fuera de rango, modelo inexistente y grafo con ciclos
+        assert!(parse(&vox_file(&[chunk(b"XYZI", &int(0))])).is_err());
+        assert!(parse(&vox_file(&[chunk(b"SIZE", &[int(1), int(1), int(300)].concat())])).is_err());
+        let size = chunk(b"SIZE", &[int(1), int(1), int(1)].concat());
+        let voxels = chunk(b"XYZI", &int(0));
+        let shape = chunk(b"nSHP", &[int(0), dict(&[]), int(1), int(5), dict(&[])].concat());
+        assert!(load_bytes("sin_modelo", &vox_file(&[size.clone(), voxels.clone(), shape]), &mut assets).is_err());
+        let cycle = chunk(b"nTRN", &[int(0), dict(&[]), int(0), int(-1), int(-1), int(0)].concat());
+        assert!(load_bytes("ciclo", &vox_file(&[size, voxels, cycle]), &mut assets).is_err());
+    }
+
+    #[test]
+    fn la_grilla_valida_paleta_y_transformacion() {
+        let model = Arc::new(VoxelModel::new([1, 1, 1]));
+        let empty = Arc::new(Material::black());
+        let palette = |length| Arc::new(vec![empty.clone(); length]);
+        assert!(VoxelGrid::new(model.clone(), palette(256), Mat3::identity(), Vec3::zeros()).is_ok());
+        assert!(VoxelGrid::new(model.clone(), palette(2), Mat3::identity(), Vec3::zeros()).is_err());
+        assert!(VoxelGrid::new(model, palette(256), Mat3::identity() * 0.0, Vec3::zeros()).is_err());
+
+        // Con voxel_size 0 la carga da error en vez de una grilla sin volumen
+        let path = std::env::temp_dir().join(format!("proyecto2raytracing_{}_sin_tamano.vox", std::process::id()));
+        std::fs::write(&path, fixture()).unwrap();
+        let result = load(&path, &mut Assets::new(), 0.0);
+        let _ = std::fs::remove_file(&path);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use std::sync::Arc;
+use nalgebra_glm::{Mat3, Vec3};
+use crate::cube::next_object_id;
+use crate::group::invert;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::ray_intersect::{Aabb, RayIntersect, Hit, Occlusion, count_intersection_test, face_axes, PARALLEL_EPSILON};
+
+// Voxeles de un modelo: un índice de paleta por celda, 0 si está vacía
+#[derive(Debug, Clone)]
+pub struct VoxelModel {
+    pub size: [usize; 3],
+    cells: Vec<u8>,
+}
+
+impl VoxelModel {
+    pub fn new(size: [usize; 3]) -> Self {
+        VoxelModel { size, cells: vec![0; size[0] * size[1] * size[2]] }
+    }
+
+    pub fn set(&mut self, [x, y, z]: [usize; 3], index: u8) {
+        if x < self.size[0] && y < self.size[1] && z < self.size[2] {
+            self.cells[(z * self.size[1] + y) * self.size[0] + x] = index;
+        }
+    }
+
+    pub fn get(&self, [x, y, z]: [usize; 3]) -> u8 {
+        self.cells[(z * self.size[1] + y) * self.size[0] + x]
+    }
+
+    pub fn cells(&self) -> &[u8] {
+        &self.cells
+    }
+}
+
+// Cruce del rayo con el borde entre dos celdas de distinto contenido
+struct Crossing {
+    t: f32,
+    axis: usize,
+    step: f32, // sentido del rayo sobre `axis`
+    from: u8,  // lo que había antes del borde
+    to: u8,
+}
+
+// Grilla de voxeles recorrida con DDA. La celda (i, j, k) ocupa [i, i+1] x [j, j+1] x [k, k+1]
+// en espacio local; `transform` y `translation` la llevan al mundo. La transformación
+// debe ser una rotación (o reflexión) por una escala, como las de MagicaVoxel.
+pub struct VoxelGrid {
+    pub model: Arc<VoxelModel>,
+    pub materials: Arc<Vec<Arc<Material>>>, // uno por índice de paleta
+    transform: Mat3,
+    inverse: Mat3,
+    translation: Vec3,
+    pub id: u32,
+}
+
+impl VoxelGrid {
+    // `materials` tiene que cubrir toda la paleta (256 entradas) y `transform` ser invertible
+    pub fn new(
+        model: Arc<VoxelModel>,
+        materials: Arc<Vec<Arc<Material>>>,
+        transform: Mat3,
+        translation: Vec3,
+    ) -> Result<Self, String> {
+        if materials.len() != 256 {
+            return Err(format!("la grilla necesita 256 materiales, uno por índice de paleta; tiene {}", materials.len()));
+        }
+        let inverse = invert(&transform).ok_or_else(|| "la transformación de la grilla no es invertible".to_string())?;
+        Ok(VoxelGrid { model, materials, transform, inverse, translation, id: next_object_id() })
+    }
+
+    // La caja de la grilla en espacio local
+    fn local_bounds(&self) -> Aabb {
+        let [x, y, z] = self.model.size;
+        Aabb::new(Vec3::zeros(), Vec3::new(x as f32, y as f32, z as f32))
+    }
+
+    fn material(&self, index: u8) -> &Material {
+        &self.materials[index as usize]
+    }
+
+    // Recorre las celdas que cruza el rayo dentro de [t_min, t_max] y llama a
+    // `visit` en cada cambio de contenido, hasta que devuelva false. Si el origen
+    // está dentro de un voxel, `from` del primer cruce es ese voxel.
+    fn march(&self, ray: &Ray, mut visit: impl FnMut(Crossing) -> bool) {
+        count_intersection_test();
+        if self.model.size.contains(&0) {
+            return;
+        }
+        let local_ray = ray.transformed(&self.inverse, &self.translation);
+        let (origin, direction) = (local_ray.origin, local_ray.direction);
+
+        // Entrada y salida de la caja de la grilla
+        let Some((t_near, near_axis, t_far, _)) = self.local_bounds().slab(&local_ray) else {
+            return;
+        };
+        let t_start = t_near.max(ray.t_min);
+        if t_start > t_far.min(ray.t_max) {
+            return;
+        }
+
+        let start = origin + direction * t_start;
+        let mut cell = [0usize; 3];
+        for axis in 0..3 {
+            cell[axis] = (start[axis].floor().max(0.0) as usize).min(self.model.size[axis] - 1);
+        }
+
+        // Sin origen dentro de la caja se entra desde el vacío por la cara `near_axis`
+        let mut current = if t_near < ray.t_min { self.model.get(cell) } else { 0 };
+        if current == 0 {
+            let value = self.model.get(cell);
+            if value != 0 {
+                let step = direction[near_axis].signum();
+                if !visit(Crossing { t: t_start, axis: near_axis, step, from: 0, to: value }) {
+                    return;
+                }
+                current = value;
+            }
+        }
+
+        let mut step = [0isize; 3];
+        let mut t_next = [f32::INFINITY; 3];
+        let mut t_delta = [f32::INFINITY; 3];
+        for axis in 0..3 {
+            if direction[axis].abs() < PARALLEL_EPSILON {
+                continue;
+            }
+            step[axis] = if direction[axis] > 0.0 { 1 } else { -1 };
+            let boundary = cell[axis] as f32 + if step[axis] > 0 { 1.0 } else { 0.0 };
+            t_next[axis] = (boundary - origin[axis]) / direction[axis];
+            t_delta[axis] = (1.0 / direction[axis]).abs();
+        }
+
+        loop {
+            let axis = (0..3).min_by(|&a, &b| t_next[a].total_cmp(&t_next[b])).unwrap_or(0);
+            let t = t_next[axis];
+            if !t.is_finite() || t > ray.t_max {
+                return;
+            }
+            let next = cell[axis] as isize + step[axis];
+            let value = if next < 0 || next >= self.model.size[axis] as isize {
+                0
+            } else {
+                cell[axis] = next as usize;
+                self.model.get(cell)
+            };
+            if value != current {
+                if !visit(Crossing { t, axis, step: step[axis] as f32, from: current, to: value }) {
+                    return;
+                }
+                current = value;
+            }
+            if value == 0 && (next < 0 || next >= self.model.size[axis] as isize) {
+                return; // Salió de la grilla
+            }
+            t_next[axis] += t_delta[axis];
+        }
+    }
+}
+
+impl RayIntersect for VoxelGrid {
+    fn ray_intersect(&self, ray: &Ray) -> Option<Hit<'_>> {
+        let mut found = None;
+        self.march(ray, |crossing| {
+            found = Some(crossing);
+            false
+        });
+        let crossing = found?;
+
+        // Al pasar a otro voxel se ve su cara; al pasar al vacío, la salida del voxel actual
+        let front_face = crossing.to != 0;
+        let index = if front_face { crossing.to } else { crossing.from };
+        let mut local_normal = Vec3::zeros();
+        local_normal[crossing.axis] = if front_face { -crossing.step } else { crossing.step };
+
+        let point = ray.at(crossing.t);
+        let local = self.inverse * (point - self.translation);
+        let (u_axis, v_axis) = face_axes(crossing.axis);
+
+        Some(Hit {
+            point,
+            normal: (self.transform * local_normal).normalize(),
+            distance: crossing.t,
+            front_face,
+            uv: (local[u_axis].rem_euclid(1.0), local[v_axis].rem_euclid(1.0)),
+            object_id: self.id,
+            material: self.material(index),
+        })
+    }
+
+    fn occlusion(&self, ray: &Ray) -> Occlusion {
+        let mut occlusion = Occlusion::None;
+        self.march(ray, |crossing| {
+            if crossing.to == 0 {
+                return true;
+            }
+            occlusion = if self.material(crossing.to).albedo[3] > 0.0 {
+                Occlusion::Transmissive
+            } else {
+                Occlusion::Opaque
+            };
+            occlusion != Occlusion::Opaque
+        });
+        occlusion
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(self.local_bounds().transformed(&self.transform, &self.translation))
+    }
+}
@@ -0,0 +1,171 @@
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+use std::sync::Arc;
+use crate::ray::Ray;
+use crate::ray_intersect::{Aabb, RayIntersect, Hit, Occlusion};
+use crate::cube::Cube;
+use crate::material::Material;
+
+// Una ola de Gerstner sobre el plano XZ
+#[derive(Debug, Clone, Copy)]
+pub struct GerstnerWave {
+    pub direction: (f32, f32), // Dirección de avance (x, z), normalizada
+    pub wavelength: f32,
+    pub amplitude: f32,
+    pub steepness: f32,        // 0 = ola senoidal, 1 = cresta afilada
+    pub speed: f32,
+}
+
+impl GerstnerWave {
+    pub fn new(direction: (f32, f32), wavelength: f32, amplitude: f32, steepness: f32, speed: f32) -> Self {
+        let length = (direction.0 * direction.0 + direction.1 * direction.1).sqrt().max(1e-6);
+        GerstnerWave {
+            direction: (direction.0 / length, direction.1 / length),
+            wavelength,
+            amplitude,
+            steepness,
+            speed,
+        }
+    }
+
+    fn frequency(&self) -> f32 {
+        2.0 * PI / self.wavelength
+    }
+
+    fn phase(&self, x: f32, z: f32, time: f32) -> f32 {
+        let k = self.frequency();
+        k * (self.direction.0 * x + self.direction.1 * z) - k * self.speed * time
+    }
+}
+
+// Superficie de agua animada: una cuadrícula de cubos cuyas caras superiores
+// toman la normal (y opcionalmente la altura) de una suma de olas de Gerstner
+pub struct Water {
+    pub cells: Vec<Cube>,
+    pub waves: Vec<GerstnerWave>,
+    pub animate_heights: bool,
+    time: f32,
+}
+
+impl Water {
+    pub fn new(cells: Vec<Cube>, waves: Vec<GerstnerWave>) -> Self {
+        Water {
+            cells,
+            waves,
+            animate_heights: false,
+            time: 0.0,
+        }
+    }
+
+    pub fn create_cuadricula(width: usize, depth: usize, cube_size: f32, material: Arc<Material>, offset_x: f32, offset_y: f32, offset_z: f32) -> Self {
+        let mut cells = Vec::new();
+
+        for x in 0..width {
+            for z in 0..depth {
+                let min = Vec3::new(
+                    x as f32 * cube_size + offset_x,
+                    offset_y,
+                    z as f32 * cube_size + offset_z
+                );
+                let max = min + Vec3::new(cube_size, cube_size, cube_size);
+
+                cells.push(Cube::new(min, max, material.clone()));
+            }
+        }
+
+        Water::new(cells, Vec::new())
+    }
+
+    pub fn with_waves(mut self, waves: Vec<GerstnerWave>) -> Self {
+        self.waves = waves;
+        self
+    }
+
+    // Las celdas suben y bajan con las olas, además de cambiar la normal
+    pub fn with_animated_heights(mut self, animate_heights: bool) -> Self {
+        self.animate_heights = animate_heights;
+        self
+    }
+
+    pub fn height_at(&self, x: f32, z: f32) -> f32 {
+        self.waves
+            .iter()
+            .map(|wave| wave.amplitude * wave.phase(x, z, self.time).sin())
+            .sum()
+    }
+
+    pub fn normal_at(&self, x: f32, z: f32) -> Vec3 {
+        let mut normal = Vec3::new(0.0, 1.0, 0.0);
+
+        for wave in &self.waves {
+            let phase = wave.phase(x, z, self.time);
+            let wa = wave.frequency() * wave.amplitude;
+            normal.x -= wave.direction.0 * wa * phase.cos();
+            normal.z -= wave.direction.1 * wa * phase.cos();
+            normal.y -= wave.steepness * wa * phase.sin();
+        }
+
+        normal.normalize()
+    }
+
+    fn cell_height(&self, cell: &Cube) -> f32 {
+        if !self.animate_heights {
+            return 0.0;
+        }
+        let center = (cell.min + cell.max) * 0.5;
+        self.height_at(center.x, center.z)
+    }
+}
+
+impl RayIntersect for Water {
+    fn ray_intersect(&self, ray: &Ray) -> Option<Hit<'_>> {
+        let mut ray = *ray;
+        let mut closest = None;
+
+        for cell in &self.cells {
+            let lift = Vec3::new(0.0, self.cell_height(cell), 0.0);
+
+            if let Some(mut hit) = cell.ray_intersect(&ray.translated(&lift)) {
+                hit.point += lift;
+                if hit.normal.y > 0.5 {
+                    hit.normal = self.normal_at(hit.point.x, hit.point.z);
+                }
+                ray.t_max = hit.distance;
+                closest = Some(hit);
+            }
+        }
+
+        closest
+    }
+
+    fn occlusion(&self, ray: &Ray) -> Occlusion {
+        let mut occlusion = Occlusion::None;
+
+        for cell in &self.cells {
+            let lift = Vec3::new(0.0, self.cell_height(cell), 0.0);
+            occlusion = occlusion.max(cell.occlusion(&ray.translated(&lift)));
+            if occlusion == Occlusion::Opaque {
+                break;
+            }
+        }
+
+        occlusion
+    }
+
+    fn update(&mut self, time: f32) {
+        self.time = time;
+        for cell in &mut self.cells {
+            cell.update(time);
+        }
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.cells
+            .iter()
+            .map(|cell| {
+                let lift = Vec3::new(0.0, self.cell_height(cell), 0.0);
+                Aabb::new(cell.min, cell.max).translated(&lift)
+            })
+            .reduce(|a, b| a.union(&b))
+    }
+}
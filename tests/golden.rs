@@ -0,0 +1,309 @@
+// Pruebas de regresión por imagen: cada escena de referencia se renderiza a baja
+// resolución y se compara con su imagen dorada en tests/golden/. Si no coincide se
+// escriben la imagen obtenida y un mapa de diferencias para revisarlas.
+//
+// Para aceptar los cambios como nuevas referencias:
+//     GOLDEN_BLESS=1 cargo test --test golden
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use image::{Rgba, RgbaImage};
+use nalgebra_glm::{Mat3, Vec3};
+
+use proyecto2raytracing::ambient::AmbientLight;
+use proyecto2raytracing::media::{Fog, Medium};
+use proyecto2raytracing::mesh::{Mesh, Triangle};
+use proyecto2raytracing::voxel::{VoxelGrid, VoxelModel};
+use proyecto2raytracing::water::{GerstnerWave, Water};
+use proyecto2raytracing::{
+    Assets, Camera, Color, Cube, Grid, Group, Light, Material, RayIntersect, RenderSettings, Renderer, Scene,
+};
+
+const WIDTH: usize = 64;
+const HEIGHT: usize = 48;
+
+// Diferencia por canal que se considera ruido numérico y no un cambio
+const PIXEL_TOLERANCE: u8 = 8;
+// Fracción de píxeles que puede pasarse de esa tolerancia
+const MAX_DIFFERENT_PIXELS: f64 = 0.005;
+const MIN_PSNR: f64 = 40.0;
+
+fn golden_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn failure_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden")
+}
+
+fn blessing() -> bool {
+    std::env::var_os("GOLDEN_BLESS").is_some_and(|value| value != "0")
+}
+
+fn render(scene: &Scene) -> RgbaImage {
+    let mut renderer = Renderer::new(RenderSettings {
+        width: WIDTH,
+        height: HEIGHT,
+        ..RenderSettings::default()
+    });
+    renderer.render(scene, &scene.camera).to_rgba_image()
+}
+
+struct Comparison {
+    different_pixels: usize,
+    psnr: f64,
+    diff: RgbaImage,
+}
+
+fn compare(expected: &RgbaImage, actual: &RgbaImage) -> Comparison {
+    let mut different_pixels = 0;
+    let mut squared_error = 0.0;
+    let diff = RgbaImage::from_fn(actual.width(), actual.height(), |x, y| {
+        let (a, b) = (expected.get_pixel(x, y), actual.get_pixel(x, y));
+        let delta = [0, 1, 2].map(|c| a[c].abs_diff(b[c]));
+        squared_error += delta.iter().map(|&d| (d as f64).powi(2)).sum::<f64>();
+        if delta.iter().any(|&d| d > PIXEL_TOLERANCE) {
+            different_pixels += 1;
+        }
+        // Las diferencias se amplifican para que se vean a simple vista
+        Rgba([delta[0].saturating_mul(4), delta[1].saturating_mul(4), delta[2].saturating_mul(4), 255])
+    });
+
+    let mse = squared_error / (3 * actual.width() * actual.height()) as f64;
+    let psnr = if mse == 0.0 { f64::INFINITY } else { 10.0 * (255.0 * 255.0 / mse).log10() };
+    Comparison { different_pixels, psnr, diff }
+}
+
+fn check_golden(name: &str, actual: RgbaImage) {
+    let golden_path = golden_dir().join(format!("{}.png", name));
+    if blessing() {
+        std::fs::create_dir_all(golden_dir()).unwrap();
+        actual.save(&golden_path).unwrap();
+        return;
+    }
+
+    let expected = match image::open(&golden_path) {
+        Ok(image) => image.to_rgba8(),
+        Err(error) => panic!(
+            "{}: no se pudo leer la imagen dorada ({}); genérala con GOLDEN_BLESS=1",
+            golden_path.display(),
+            error
+        ),
+    };
+    assert_eq!(
+        expected.dimensions(),
+        actual.dimensions(),
+        "{}: la imagen dorada tiene otro tamaño",
+        name
+    );
+
+    let comparison = compare(&expected, &actual);
+    let limit = (MAX_DIFFERENT_PIXELS * (WIDTH * HEIGHT) as f64).floor() as usize;
+    if comparison.different_pixels > limit || comparison.psnr < MIN_PSNR {
+        let dir = failure_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let actual_path = dir.join(format!("{}_actual.png", name));
+        let diff_path = dir.join(format!("{}_diff.png", name));
+        actual.save(&actual_path).unwrap();
+        comparison.diff.save(&diff_path).unwrap();
+        panic!(
+            "{}: {} píxeles distintos (máximo {}), PSNR {:.1} dB (mínimo {}).\nObtenida: {}\nDiferencias: {}",
+            name,
+            comparison.different_pixels,
+            limit,
+            comparison.psnr,
+            MIN_PSNR,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+// Escena sin niebla ni oclusión ambiental. El sol queda en `sun` mientras no se
+// llame a set_time, que lo mueve con el ciclo de día.
+fn scene(objects: Vec<Box<dyn RayIntersect>>, sun: Vec3, camera: Camera) -> Scene {
+    let lights = vec![Light::new(sun, Color::new(255, 255, 255), 1.0)];
+    Scene::new(objects, lights, camera)
+        .with_sun(0)
+        .with_ambient(AmbientLight::new(Color::new(102, 153, 255), 0.25))
+}
+
+fn floor(assets: &mut Assets) -> Grid {
+    let piso = assets.add_material("piso", Material::new(
+        Color::new(200, 200, 200),
+        50.0,
+        [0.9, 0.2, 0.0, 0.0],
+        1.0,
+        None,
+    )).unwrap();
+    Grid::create_cuadricula(8, 8, 0.5, piso, -2.0, -0.5, -2.0)
+}
+
+// Difuso, especular y sombras duras
+#[test]
+fn cubos_opacos() {
+    let mut assets = Assets::new();
+    let rojo = assets.add_material("rojo", Material::new(Color::new(200, 40, 40), 50.0, [0.9, 0.5, 0.0, 0.0], 1.0, None)).unwrap();
+    let verde = assets.add_material("verde", Material::new(Color::new(40, 180, 60), 10.0, [0.9, 0.1, 0.0, 0.0], 1.0, None)).unwrap();
+
+    let objects: Vec<Box<dyn RayIntersect>> = vec![
+        Box::new(floor(&mut assets)),
+        Box::new(Cube::new(Vec3::new(-0.9, 0.0, -0.4), Vec3::new(-0.1, 0.8, 0.4), rojo)),
+        Box::new(Cube::new(Vec3::new(0.3, 0.0, -0.2), Vec3::new(0.8, 0.5, 0.3), verde)),
+    ];
+    let camera = Camera::new(Vec3::new(0.5, 1.5, 3.0), Vec3::new(0.0, 0.2, 0.0), Vec3::new(0.0, 1.0, 0.0));
+    let scene = scene(objects, Vec3::new(2.0, 5.0, 3.0), camera);
+
+    check_golden("cubos_opacos", render(&scene));
+}
+
+// Reflexión, refracción con medio interior y sombras teñidas por el vidrio
+#[test]
+fn espejo_y_vidrio() {
+    let mut assets = Assets::new();
+    let espejo = assets.add_material("espejo", Material::new(Color::new(255, 255, 255), 1000.0, [0.1, 0.8, 0.8, 0.0], 1.0, None)).unwrap();
+    let vidrio = assets.add_material("vidrio", Material::new(
+        Color::new(120, 200, 255),
+        500.0,
+        [0.1, 0.5, 0.1, 0.8],
+        1.5,
+        None,
+    ).with_medium(Medium::new(Color::new(20, 90, 110), 0.8))).unwrap();
+    let textura = assets.texture(concat!(env!("CARGO_MANIFEST_DIR"), "/palmeratext.png")).unwrap();
+    let madera = assets.add_material("madera", Material::new(Color::new(161, 102, 47), 100.0, [0.9, 0.3, 0.0, 0.0], 1.0, Some(textura))).unwrap();
+
+    let objects: Vec<Box<dyn RayIntersect>> = vec![
+        Box::new(floor(&mut assets)),
+        Box::new(Cube::new(Vec3::new(-1.2, 0.0, -0.8), Vec3::new(-0.4, 1.0, -0.6), espejo)),
+        Box::new(Cube::new(Vec3::new(-0.2, 0.0, 0.0), Vec3::new(0.4, 0.6, 0.6), vidrio)),
+        Box::new(Cube::new(Vec3::new(0.3, 0.0, -0.9), Vec3::new(0.9, 0.6, -0.3), madera)),
+    ];
+    let camera = Camera::new(Vec3::new(0.8, 1.2, 2.5), Vec3::new(-0.1, 0.3, -0.2), Vec3::new(0.0, 1.0, 0.0));
+    let scene = scene(objects, Vec3::new(1.0, 6.0, 2.0), camera);
+
+    check_golden("espejo_y_vidrio", render(&scene));
+}
+
+// Olas animadas, niebla con rayos de luz, oclusión ambiental, emisión y el ciclo de día
+#[test]
+fn agua_con_niebla() {
+    let mut assets = Assets::new();
+    let agua = assets.add_material("agua", Material::new(
+        Color::new(0, 255, 255),
+        500.0,
+        [0.1, 0.5, 0.3, 0.8],
+        1.33,
+        None,
+    ).with_medium(Medium::new(Color::new(20, 90, 110), 1.5))).unwrap();
+    let textura = assets.texture(concat!(env!("CARGO_MANIFEST_DIR"), "/arena.png")).unwrap();
+    let arena = assets.add_material("arena", Material::new(Color::new(203, 189, 147), 1000.0, [0.9, 0.5, 0.1, 0.0], 1.0, Some(textura))).unwrap();
+    let farol = assets.add_material("farol", Material::new(
+        Color::new(255, 180, 80),
+        10.0,
+        [0.9, 0.1, 0.0, 0.0],
+        1.0,
+        None,
+    ).with_emission(Color::new(255, 180, 80), 1.0)).unwrap();
+
+    let olas = vec![
+        GerstnerWave::new((1.0, 0.3), 1.2, 0.015, 0.5, 0.4),
+        GerstnerWave::new((0.4, 1.0), 0.7, 0.008, 0.4, 0.3),
+    ];
+    let farol = Cube::new(Vec3::new(0.9, 0.3, 0.4), Vec3::new(1.0, 0.4, 0.5), farol);
+    let luces_farol = farol.emissive_lights(1);
+
+    let objects: Vec<Box<dyn RayIntersect>> = vec![
+        Box::new(Water::create_cuadricula(4, 4, 0.3, agua, -1.2, 0.0, 0.0).with_waves(olas).with_animated_heights(true)),
+        Box::new(Grid::create_cuadricula(4, 4, 0.3, arena, 0.0, 0.0, 0.0)),
+        Box::new(farol),
+    ];
+    let camera = Camera::new(Vec3::new(0.0, 1.2, 3.0), Vec3::new(0.0, 0.2, 0.6), Vec3::new(0.0, 1.0, 0.0));
+    let mut scene = scene(objects, Vec3::new(0.0, 10.0, 0.0), camera)
+        .with_ambient(AmbientLight::new(Color::new(102, 153, 255), 0.25).with_occlusion(8, 0.3))
+        .with_fog(
+            Fog::new(Color::new(190, 205, 225), 0.02)
+                .with_height_fog(0.04, 2.0, 0.0)
+                .with_light_shafts(0.6, 8, 0.6),
+        );
+    scene.lights.extend(luces_farol);
+
+    scene.set_time(7.5);
+
+    check_golden("agua_con_niebla", render(&scene));
+}
+
+// Grilla de voxeles rotada y escalada, con voxeles opacos y de vidrio vecinos
+#[test]
+fn voxeles() {
+    let mut assets = Assets::new();
+    let vacio = Arc::new(Material::black());
+    let mut materials = vec![vacio; 256];
+    materials[1] = assets.add_material("piedra", Material::new(Color::new(150, 140, 130), 10.0, [0.9, 0.1, 0.0, 0.0], 1.0, None)).unwrap();
+    materials[2] = assets.add_material("vidrio", Material::new(Color::new(120, 200, 255), 500.0, [0.1, 0.5, 0.1, 0.8], 1.5, None)).unwrap();
+
+    let mut model = VoxelModel::new([4, 3, 4]);
+    for x in 0..4 {
+        for z in 0..4 {
+            model.set([x, 0, z], 1);
+        }
+    }
+    model.set([0, 1, 0], 1);
+    model.set([0, 2, 0], 1);
+    model.set([1, 1, 0], 2);
+    model.set([2, 1, 1], 2);
+    model.set([2, 1, 2], 2);
+
+    // Un cuarto de vuelta alrededor de Y, con voxeles de 0.25
+    let rotation = Mat3::new(0.0, 0.0, 1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 0.0) * 0.25;
+    let grid = VoxelGrid::new(Arc::new(model), Arc::new(materials), rotation, Vec3::new(-0.5, 0.0, 0.5)).unwrap();
+
+    let objects: Vec<Box<dyn RayIntersect>> = vec![Box::new(floor(&mut assets)), Box::new(grid)];
+    let camera = Camera::new(Vec3::new(1.2, 1.4, 2.2), Vec3::new(0.0, 0.2, 0.0), Vec3::new(0.0, 1.0, 0.0));
+    let scene = scene(objects, Vec3::new(2.0, 5.0, 3.0), camera);
+
+    check_golden("voxeles", render(&scene));
+}
+
+// Mallas de triángulos con normales suavizadas dentro de un grupo rotado y escalado
+#[test]
+fn malla_transformada() {
+    let mut assets = Assets::new();
+    let azul = assets.add_material("azul", Material::new(Color::new(60, 90, 200), 100.0, [0.9, 0.3, 0.1, 0.0], 1.0, None)).unwrap();
+    let vidrio = assets.add_material("vidrio", Material::new(Color::new(220, 240, 255), 500.0, [0.1, 0.5, 0.1, 0.8], 1.5, None)).unwrap();
+
+    // Tetraedro con la normal de cada vértice apuntando desde el centro
+    let corners = [
+        Vec3::new(1.0, 1.0, 1.0),
+        Vec3::new(1.0, -1.0, -1.0),
+        Vec3::new(-1.0, 1.0, -1.0),
+        Vec3::new(-1.0, -1.0, 1.0),
+    ];
+    let faces = [[0, 1, 2], [0, 3, 1], [0, 2, 3], [1, 3, 2]];
+    let tetraedro = |smooth: bool| {
+        faces
+            .iter()
+            .map(|&[a, b, c]| {
+                let mut triangle = Triangle::new([corners[a], corners[b], corners[c]]);
+                if smooth {
+                    triangle.normals = Some([corners[a].normalize(), corners[b].normalize(), corners[c].normalize()]);
+                }
+                triangle
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let rotation = Mat3::new(0.8, 0.0, 0.6, 0.0, 1.0, 0.0, -0.6, 0.0, 0.8) * 0.3;
+    let solido = Group::new(vec![Box::new(Mesh::new(tetraedro(true), azul))], Vec3::new(-0.5, 0.3, 0.0))
+        .with_transform(rotation)
+        .unwrap();
+    let transparente = Group::new(vec![Box::new(Mesh::new(tetraedro(false), vidrio))], Vec3::new(0.5, 0.3, 0.2))
+        .with_transform(rotation.transpose())
+        .unwrap();
+
+    let objects: Vec<Box<dyn RayIntersect>> = vec![Box::new(floor(&mut assets)), Box::new(solido), Box::new(transparente)];
+    let camera = Camera::new(Vec3::new(0.3, 1.3, 2.6), Vec3::new(0.0, 0.2, 0.0), Vec3::new(0.0, 1.0, 0.0));
+    let scene = scene(objects, Vec3::new(2.0, 5.0, 3.0), camera);
+
+    check_golden("malla_transformada", render(&scene));
+}